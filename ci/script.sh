@@ -11,6 +11,8 @@ main() {
     if [ $TARGET = x86_64-unknown-linux-gnu ]; then
         cargo test --target $TARGET
         cargo test --features custom-error-messages --target $TARGET
+        cargo test --features error-position --target $TARGET
+        cargo test --features error-path --target $TARGET
 
         return
     fi
@@ -0,0 +1,175 @@
+/// Deserializer configuration, handed to `de::Deserializer::with_config`
+///
+/// The deserializer can be told to reject input that exceeds some limits, for example when parsing
+/// untrusted data on a device with little stack:
+///
+/// ```
+/// use serde_json_core::de::{Deserializer, Error};
+/// use serde_json_core::Config;
+///
+/// let config = Config::new().max_depth(2).max_string_len(8);
+///
+/// let mut de = Deserializer::with_config(b"[[[1]]]", config);
+/// let value: Result<[[[u8; 1]; 1]; 1], _> = serde::Deserialize::deserialize(&mut de);
+/// assert_eq!(value, Err(Error::DepthLimitExceeded));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    pub(crate) max_depth: usize,
+    pub(crate) max_string_len: usize,
+    pub(crate) max_elements: usize,
+    pub(crate) max_members: usize,
+    pub(crate) strict: bool,
+    pub(crate) lenient: bool,
+    pub(crate) deny_duplicate_keys: bool,
+}
+
+impl Config {
+    /// Creates the default configuration
+    // `usize::MAX` is newer than our MSRV
+    #[allow(clippy::legacy_numeric_constants)]
+    pub fn new() -> Self {
+        Config {
+            max_depth: usize::max_value(),
+            max_string_len: usize::max_value(),
+            max_elements: usize::max_value(),
+            max_members: usize::max_value(),
+            strict: false,
+            lenient: false,
+            deny_duplicate_keys: false,
+        }
+    }
+
+    /// Sets how many arrays and objects can be nested in each other when deserializing (default:
+    /// no limit). Deeper input is reported as `de::Error::DepthLimitExceeded`.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets the maximum length in bytes of strings and object keys, as written in the input, when
+    /// deserializing (default: no limit). Longer strings are reported as
+    /// `de::Error::StringTooLong`.
+    pub fn max_string_len(mut self, len: usize) -> Self {
+        self.max_string_len = len;
+        self
+    }
+
+    /// Sets the maximum number of elements of an array when deserializing (default: no limit).
+    /// Longer arrays are reported as `de::Error::TooManyElements`.
+    ///
+    /// Arrays that are skipped, like unknown struct fields, are checked too; while this limit is
+    /// set, skipped values can be nested at most 32 levels deep.
+    pub fn max_elements(mut self, len: usize) -> Self {
+        self.max_elements = len;
+        self
+    }
+
+    /// Sets the maximum number of members of an object when deserializing (default: no limit).
+    /// Larger objects are reported as `de::Error::TooManyMembers`.
+    ///
+    /// Objects that are skipped, like unknown struct fields, are checked too; while this limit is
+    /// set, skipped values can be nested at most 32 levels deep.
+    pub fn max_members(mut self, len: usize) -> Self {
+        self.max_members = len;
+        self
+    }
+
+    /// Makes the deserializer only accept input that conforms to RFC 8259 (default: `false`)
+    ///
+    /// In strict mode numbers must follow the JSON number grammar, so `1.`, `.5`, `+1` and `01`
+    /// are reported as `de::Error::InvalidNumber`; escape sequences are checked in every string,
+    /// not just in skipped ones; and raw control characters inside strings are reported as
+    /// `de::Error::ControlCharacterInString`.
+    ///
+    /// Strict and lenient mode exclude each other: enabling strict mode disables lenient mode, so
+    /// whichever of the two was enabled last applies.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        if strict {
+            self.lenient = false;
+        }
+        self
+    }
+
+    /// Makes the deserializer accept some JSON5 / JSONC extensions, for hand-edited input
+    /// (default: `false`)
+    ///
+    /// In lenient mode the deserializer also accepts `//` and `/* */` comments, trailing commas in
+    /// arrays and objects, single-quoted strings, unquoted object keys made of ASCII letters,
+    /// digits, `_` and `$`, and hexadecimal integers like `0x1F`. Like other strings, single-quoted
+    /// strings are returned with their escape sequences, such as `\'`, left in place.
+    ///
+    /// Lenient mode applies to the `Deserializer`, `Reader` and `RawValue::parse_with_config`;
+    /// `de::validate`, `minify`, `pretty_print` and `pointer` always take standard JSON. Enabling
+    /// lenient mode disables strict mode, see `strict`.
+    ///
+    /// ```
+    /// use serde_derive::Deserialize;
+    /// use serde_json_core::de::Deserializer;
+    /// use serde_json_core::Config;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Uart<'a> {
+    ///     name: &'a str,
+    ///     baud: u32,
+    ///     flags: [u8; 2],
+    /// }
+    ///
+    /// let input = br#"{
+    ///     // the console
+    ///     name: 'uart0',
+    ///     baud: 115200, /* fixed */
+    ///     flags: [0x01, 0x80,],
+    /// }"#;
+    ///
+    /// let mut de = Deserializer::with_config(input, Config::new().lenient(true));
+    /// let uart: Uart<'_> = serde::Deserialize::deserialize(&mut de).unwrap();
+    /// de.end().unwrap();
+    /// assert_eq!(
+    ///     uart,
+    ///     Uart {
+    ///         name: "uart0",
+    ///         baud: 115200,
+    ///         flags: [1, 128],
+    ///     }
+    /// );
+    /// ```
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        if lenient {
+            self.strict = false;
+        }
+        self
+    }
+
+    /// Makes the deserializer reject objects that have the same key more than once, as
+    /// `de::Error::DuplicateKey` (default: `false`)
+    ///
+    /// Keys are compared as written in the input, so `"a"` and `"\u0061"` are different keys.
+    /// Up to 32 keys per object are remembered, in a sorted set that takes two `usize`s per key
+    /// of stack for each object being deserialized; objects with more keys are reported as
+    /// `de::Error::KeyLimitExceeded`. Nothing is reserved while the option is off.
+    ///
+    /// Objects that are skipped, like unknown struct fields, are checked too. The objects being
+    /// skipped at once share one set of 32 keys, and can be nested at most 32 levels deep.
+    pub fn deny_duplicate_keys(mut self, deny: bool) -> Self {
+        self.deny_duplicate_keys = deny;
+        self
+    }
+
+    /// Whether skipping a value takes more than checking its syntax
+    // `usize::MAX` is newer than our MSRV
+    #[allow(clippy::legacy_numeric_constants)]
+    pub(crate) fn limits_skipped_values(&self) -> bool {
+        self.max_elements != usize::max_value()
+            || self.max_members != usize::max_value()
+            || self.deny_duplicate_keys
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
+    }
+}
@@ -0,0 +1,295 @@
+use core::fmt::Write;
+
+use crate::de::{Deserializer, Error, Reader, Result, Token};
+
+/// Removes the whitespace between the tokens of the JSON document in `json`, in place
+///
+/// The document is checked to be well-formed first, like `de::validate` does, and is left
+/// untouched if it's not. Returns the length of the compacted document, which is at the start of
+/// `json`.
+///
+/// Only standard JSON is accepted: the comments, trailing commas and other extensions of
+/// `Config::lenient` are reported as errors.
+///
+/// ```
+/// let mut json = *b"{\n  \"ssid\": \"my home\",\n  \"channels\": [1, 6]\n}\n";
+/// let len = serde_json_core::de::minify(&mut json).unwrap();
+/// assert_eq!(&json[..len], br#"{"ssid":"my home","channels":[1,6]}"#);
+/// ```
+pub fn minify(json: &mut [u8]) -> Result<usize> {
+    Deserializer::new(json).validate()?;
+
+    let mut len = 0;
+    let mut in_str = false;
+    let mut escaped = false;
+    for i in 0..json.len() {
+        let c = json[i];
+        if in_str {
+            if escaped {
+                escaped = false;
+            } else if c == b'\\' {
+                escaped = true;
+            } else if c == b'"' {
+                in_str = false;
+            }
+        } else {
+            match c {
+                b' ' | b'\n' | b'\t' | b'\r' => continue,
+                b'"' => in_str = true,
+                _ => {}
+            }
+        }
+
+        json[len] = c;
+        len += 1;
+    }
+
+    Ok(len)
+}
+
+/// Writes the JSON document in `input` to `out` without whitespace between its tokens
+///
+/// The document is checked to be well-formed standard JSON first, like with `minify`, and nothing
+/// is written if it's not. Containers can be nested up to 32 levels deep, like with `Reader`. If
+/// `out` fails, `Error::WriteFailed` is returned.
+pub fn minify_to<W>(input: &[u8], out: &mut W) -> Result<()>
+where
+    W: Write,
+{
+    write_tokens(input, None, out)
+}
+
+/// Writes the JSON document in `input` to `out` with one array element or object member per line,
+/// each indented with one `indent` per level of nesting
+///
+/// The document is checked to be well-formed standard JSON first, like with `minify`, and nothing
+/// is written if it's not. Containers can be nested up to 32 levels deep, like with `Reader`. If
+/// `out` fails, `Error::WriteFailed` is returned.
+///
+/// ```
+/// use heapless::{consts::U128, String};
+///
+/// let mut out = String::<U128>::new();
+/// serde_json_core::de::pretty_print(br#"{"on":true,"rgb":[255,0,0],"tags":{}}"#, "  ", &mut out)
+///     .unwrap();
+/// assert_eq!(
+///     out,
+///     "{\n  \"on\": true,\n  \"rgb\": [\n    255,\n    0,\n    0\n  ],\n  \"tags\": {}\n}"
+/// );
+/// ```
+pub fn pretty_print<W>(input: &[u8], indent: &str, out: &mut W) -> Result<()>
+where
+    W: Write,
+{
+    write_tokens(input, Some(indent), out)
+}
+
+/// Writes the tokens of `input` to `out`, on separate lines if there's an `indent`
+fn write_tokens<W>(input: &[u8], indent: Option<&str>, out: &mut W) -> Result<()>
+where
+    W: Write,
+{
+    Deserializer::new(input).validate()?;
+
+    let mut reader = Reader::new(input);
+    // no element of the innermost container has been written yet
+    let mut first = true;
+    // the next token is the value of an object member
+    let mut after_key = false;
+
+    while let Some(token) = reader.next() {
+        let token = token?;
+        let depth = reader.depth();
+
+        match token {
+            Token::EndObject | Token::EndArray => {
+                if !first {
+                    new_line(indent, depth, out)?;
+                }
+                first = false;
+            }
+            _ if after_key => after_key = false,
+            Token::BeginObject | Token::BeginArray => {
+                if depth > 1 {
+                    element(&mut first, indent, depth - 1, out)?;
+                }
+            }
+            _ => {
+                if depth > 0 {
+                    element(&mut first, indent, depth, out)?;
+                }
+            }
+        }
+
+        match token {
+            Token::BeginObject => {
+                first = true;
+                out.write_char('{')
+            }
+            Token::EndObject => out.write_char('}'),
+            Token::BeginArray => {
+                first = true;
+                out.write_char('[')
+            }
+            Token::EndArray => out.write_char(']'),
+            Token::Key(key) => {
+                after_key = true;
+                let colon = if indent.is_some() { "\": " } else { "\":" };
+                out.write_char('"')
+                    .and_then(|_| out.write_str(key))
+                    .and_then(|_| out.write_str(colon))
+            }
+            Token::String(s) => out
+                .write_char('"')
+                .and_then(|_| out.write_str(s))
+                .and_then(|_| out.write_char('"')),
+            Token::Number(n) => out.write_str(n),
+            Token::Bool(true) => out.write_str("true"),
+            Token::Bool(false) => out.write_str("false"),
+            Token::Null => out.write_str("null"),
+        }
+        .map_err(|_| Error::WriteFailed)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the separator that goes before an element of a container, at nesting level `depth`
+fn element<W>(first: &mut bool, indent: Option<&str>, depth: usize, out: &mut W) -> Result<()>
+where
+    W: Write,
+{
+    if !*first {
+        out.write_char(',').map_err(|_| Error::WriteFailed)?;
+    }
+    *first = false;
+    new_line(indent, depth, out)
+}
+
+/// Starts a new line indented `depth` times, if there's an `indent`
+fn new_line<W>(indent: Option<&str>, depth: usize, out: &mut W) -> Result<()>
+where
+    W: Write,
+{
+    if let Some(indent) = indent {
+        out.write_char('\n').map_err(|_| Error::WriteFailed)?;
+        for _ in 0..depth {
+            out.write_str(indent).map_err(|_| Error::WriteFailed)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use heapless::{consts::*, String};
+
+    use crate::de::{minify, minify_to, pretty_print, Error};
+
+    const PRETTY: &str = r#"{
+    "name": "a \"b\" c",
+    "empty": [],
+    "nested": [
+        {
+            "x": -1.5e3
+        },
+        [
+            null,
+            false
+        ],
+        {}
+    ]
+}"#;
+
+    const COMPACT: &str =
+        r#"{"name":"a \"b\" c","empty":[],"nested":[{"x":-1.5e3},[null,false],{}]}"#;
+
+    #[test]
+    fn in_place() {
+        let mut json = [0; 256];
+        json[..PRETTY.len()].copy_from_slice(PRETTY.as_bytes());
+        let len = minify(&mut json[..PRETTY.len()]).unwrap();
+        assert_eq!(&json[..len], COMPACT.as_bytes());
+
+        let mut json = *b"[1, \"a\\\\\", 2]";
+        let len = minify(&mut json).unwrap();
+        assert_eq!(&json[..len], b"[1,\"a\\\\\",2]");
+
+        let mut json = *b"[1, 2,]";
+        assert_eq!(minify(&mut json), Err(Error::TrailingComma));
+        assert_eq!(&json, b"[1, 2,]");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut compact = String::<U128>::new();
+        minify_to(PRETTY.as_bytes(), &mut compact).unwrap();
+        assert_eq!(compact, COMPACT);
+
+        let mut pretty = String::<U256>::new();
+        pretty_print(COMPACT.as_bytes(), "    ", &mut pretty).unwrap();
+        assert_eq!(pretty, PRETTY);
+
+        let mut out = String::<U16>::new();
+        pretty_print(b" 42 ", "  ", &mut out).unwrap();
+        assert_eq!(out, "42");
+    }
+
+    #[test]
+    fn errors() {
+        let mut out = String::<U16>::new();
+        assert_eq!(
+            pretty_print(b"[1, 2", "  ", &mut out),
+            Err(Error::EofWhileParsingList)
+        );
+
+        let mut out = String::<U8>::new();
+        assert_eq!(
+            minify_to(COMPACT.as_bytes(), &mut out),
+            Err(Error::WriteFailed)
+        );
+    }
+
+    #[test]
+    fn standard_json_only() {
+        let mut json = *b"[1, // one\n 2]";
+        assert_eq!(minify(&mut json), Err(Error::ExpectedSomeValue));
+
+        let mut out = String::<U16>::new();
+        assert_eq!(
+            minify_to(b"{'a': 1}", &mut out),
+            Err(Error::KeyMustBeAString)
+        );
+
+        let mut out = String::<U16>::new();
+        assert_eq!(
+            pretty_print(b"[1, 2,]", "  ", &mut out),
+            Err(Error::TrailingComma)
+        );
+
+        for input in [
+            &b"[1., 01, 1-2]"[..],
+            b"[01]",
+            b"[1-2]",
+            b"[--5]",
+            b"[1.2.3]",
+        ]
+        .iter()
+        {
+            let mut out = String::<U16>::new();
+            assert_eq!(minify_to(input, &mut out), Err(Error::InvalidNumber));
+            let mut out = String::<U16>::new();
+            assert_eq!(
+                pretty_print(input, "  ", &mut out),
+                Err(Error::InvalidNumber)
+            );
+        }
+        let mut json = *b"[1., 01, 1-2]";
+        assert_eq!(minify(&mut json), Err(Error::InvalidNumber));
+
+        let mut out = String::<U16>::new();
+        assert_eq!(minify_to(b"[\"\\x\"]", &mut out), Err(Error::InvalidEscape));
+        assert_eq!(out, "");
+    }
+}
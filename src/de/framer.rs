@@ -0,0 +1,186 @@
+use crate::de::{Error, Result};
+
+/// Finds where top-level JSON values end in a stream of bytes that arrives in chunks
+///
+/// The framer only tracks string / escape state and bracket depth; it doesn't validate the
+/// values. Once a value has been framed, decode it with `from_slice`.
+///
+/// A top-level string, object or array is framed as soon as its closing byte arrives. A top-level
+/// number, `true`, `false` or `null` is only framed once the byte that follows it arrives.
+///
+/// ```
+/// use serde_json_core::de::Framer;
+///
+/// let mut framer = Framer::new();
+/// let (mut buf, mut len) = ([0; 32], 0);
+/// let mut values = 0;
+///
+/// for chunk in &[&b"\"hel"[..], b"lo\" \"wor", b"ld\""] {
+///     let mut chunk: &[u8] = chunk;
+///     while let Some(n) = framer.feed(chunk).unwrap() {
+///         buf[len..len + n].copy_from_slice(&chunk[..n]);
+///         let value: &str = serde_json_core::from_slice(&buf[..len + n]).unwrap();
+///         assert_eq!(value, ["hello", "world"][values]);
+///         values += 1;
+///
+///         len = 0;
+///         chunk = &chunk[n..];
+///     }
+///     buf[len..len + chunk.len()].copy_from_slice(chunk);
+///     len += chunk.len();
+/// }
+///
+/// assert_eq!(values, 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Framer {
+    state: State,
+    depth: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    /// Between values
+    Idle,
+    /// Inside an object or array, outside of strings
+    Container,
+    /// Inside a string
+    String,
+    /// After a backslash inside a string
+    Escape,
+    /// Inside a top-level number or literal
+    Scalar,
+}
+
+impl Default for Framer {
+    fn default() -> Self {
+        Framer::new()
+    }
+}
+
+impl Framer {
+    /// Creates a framer that is waiting for the start of a value
+    pub fn new() -> Self {
+        Framer {
+            state: State::Idle,
+            depth: 0,
+        }
+    }
+
+    /// Feeds the next chunk of bytes
+    ///
+    /// Returns `Some(n)` if a top-level value ends in `chunk`, where `n` is the number of bytes of
+    /// `chunk` up to the end of the value. The framer is then ready for the next value and the
+    /// rest of the chunk, `chunk[n..]`, should be fed again. Returns `None` if all of `chunk` was
+    /// consumed without completing a value.
+    ///
+    /// A closing bracket or comma outside of any value is reported as `Error::ExpectedSomeValue`.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Option<usize>> {
+        for (i, &c) in chunk.iter().enumerate() {
+            match self.state {
+                State::Idle => match c {
+                    b' ' | b'\n' | b'\t' | b'\r' | 0x1e => {}
+                    b'"' => {
+                        self.state = State::String;
+                    }
+                    b'[' | b'{' => {
+                        self.state = State::Container;
+                        self.depth = 1;
+                    }
+                    b']' | b'}' | b',' => return Err(Error::ExpectedSomeValue),
+                    _ => self.state = State::Scalar,
+                },
+                State::Container => match c {
+                    b'"' => self.state = State::String,
+                    b'[' | b'{' => self.depth += 1,
+                    b']' | b'}' => {
+                        self.depth -= 1;
+                        if self.depth == 0 {
+                            self.state = State::Idle;
+                            return Ok(Some(i + 1));
+                        }
+                    }
+                    _ => {}
+                },
+                State::String => match c {
+                    b'\\' => self.state = State::Escape,
+                    b'"' if self.depth == 0 => {
+                        self.state = State::Idle;
+                        return Ok(Some(i + 1));
+                    }
+                    b'"' => self.state = State::Container,
+                    _ => {}
+                },
+                State::Escape => self.state = State::String,
+                State::Scalar => match c {
+                    b' ' | b'\n' | b'\t' | b'\r' | 0x1e | b'"' | b'[' | b'{' | b']' | b'}'
+                    | b',' => {
+                        self.state = State::Idle;
+                        return Ok(Some(i));
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns `true` if the start of a value has been fed but not its end
+    pub fn in_value(&self) -> bool {
+        self.state != State::Idle
+    }
+
+    /// Discards any partially framed value
+    pub fn reset(&mut self) {
+        *self = Framer::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::de::{Error, Framer};
+
+    #[test]
+    fn containers() {
+        let mut framer = Framer::new();
+        assert_eq!(framer.feed(b"  {\"a\": [1, {\"b\""), Ok(None));
+        assert!(framer.in_value());
+        assert_eq!(framer.feed(b": 2}]"), Ok(None));
+        assert_eq!(framer.feed(b"}\n[]"), Ok(Some(1)));
+        assert!(!framer.in_value());
+        assert_eq!(framer.feed(b"\n[]"), Ok(Some(3)));
+    }
+
+    #[test]
+    fn strings() {
+        let mut framer = Framer::new();
+        // brackets and escaped quotes inside strings don't count
+        assert_eq!(framer.feed(br#"["]", "\"}", "\\"#), Ok(None));
+        assert_eq!(framer.feed(br#""]"#), Ok(Some(2)));
+
+        // the escape state survives chunk boundaries
+        assert_eq!(framer.feed(br#""a\"#), Ok(None));
+        assert_eq!(framer.feed(br#"""#), Ok(None));
+        assert_eq!(framer.feed(br#"""#), Ok(Some(1)));
+    }
+
+    #[test]
+    fn scalars() {
+        let mut framer = Framer::new();
+        assert_eq!(framer.feed(b"12"), Ok(None));
+        assert_eq!(framer.feed(b"3\n"), Ok(Some(1)));
+        assert_eq!(framer.feed(b"\ntrue["), Ok(Some(5)));
+    }
+
+    #[test]
+    fn errors() {
+        let mut framer = Framer::new();
+        assert_eq!(framer.feed(b" ]"), Err(Error::ExpectedSomeValue));
+
+        framer.feed(b"[[").unwrap();
+        framer.reset();
+        assert!(!framer.in_value());
+        assert_eq!(framer.feed(b"[]"), Ok(Some(2)));
+    }
+}
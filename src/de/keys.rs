@@ -0,0 +1,56 @@
+use crate::de::{Error, Result};
+
+/// How many keys of an object are remembered when looking for duplicates
+pub(crate) const KEY_LIMIT: usize = 32;
+
+/// Set of the keys read so far from an object, stored as byte ranges of the input and kept sorted
+/// by their text
+pub(crate) struct KeySet {
+    keys: [(usize, usize); KEY_LIMIT],
+    len: usize,
+}
+
+impl KeySet {
+    pub(crate) fn new() -> Self {
+        KeySet {
+            keys: [(0, 0); KEY_LIMIT],
+            len: 0,
+        }
+    }
+
+    /// Number of keys in the set
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Removes the keys added after the first `len`
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.len = len;
+    }
+
+    /// Adds the key at `start..end` of `slice`, unless one of the keys added after the first
+    /// `base` is the same
+    ///
+    /// Only the keys after `base` are kept sorted, so that the set can hold the keys of several
+    /// nested objects.
+    pub(crate) fn insert(
+        &mut self,
+        slice: &[u8],
+        base: usize,
+        start: usize,
+        end: usize,
+    ) -> Result<()> {
+        let key = &slice[start..end];
+        match self.keys[base..self.len].binary_search_by(|&(s, e)| slice[s..e].cmp(key)) {
+            Ok(_) => Err(Error::DuplicateKey),
+            Err(_) if self.len == KEY_LIMIT => Err(Error::KeyLimitExceeded),
+            Err(i) => {
+                let i = base + i;
+                self.keys[i..=self.len].rotate_right(1);
+                self.keys[i] = (start, end);
+                self.len += 1;
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,15 +1,28 @@
 use serde::de::{self, Visitor};
 
+use crate::de::keys::KeySet;
 use crate::de::{Deserializer, Error};
 
 pub struct MapAccess<'a, 'b> {
     de: &'a mut Deserializer<'b>,
     first: bool,
+    /// Number of keys read so far
+    len: usize,
+    /// Byte range of the last key, without its quotes
+    key: (usize, usize),
+    /// Keys read so far, if duplicate keys are denied
+    keys: Option<&'a mut KeySet>,
 }
 
 impl<'a, 'b> MapAccess<'a, 'b> {
-    pub(crate) fn new(de: &'a mut Deserializer<'b>) -> Self {
-        MapAccess { de, first: true }
+    pub(crate) fn new(de: &'a mut Deserializer<'b>, keys: Option<&'a mut KeySet>) -> Self {
+        MapAccess {
+            de,
+            first: true,
+            len: 0,
+            key: (0, 0),
+            keys,
+        }
     }
 }
 
@@ -40,20 +53,52 @@ impl<'a, 'de> de::MapAccess<'de> for MapAccess<'a, 'de> {
             }
         };
 
-        match peek.ok_or(Error::EofWhileParsingValue)? {
-            b'"' => seed.deserialize(MapKey { de: &mut *self.de }).map(Some),
-            b'}' => Err(Error::TrailingComma),
-            _ => Err(Error::KeyMustBeAString),
+        let peek = peek.ok_or(Error::EofWhileParsingValue)?;
+        if peek == b'}' {
+            return if self.de.config.lenient {
+                Ok(None)
+            } else {
+                Err(Error::TrailingComma)
+            };
+        }
+        if !self.de.is_key_start(peek) {
+            return Err(Error::KeyMustBeAString);
+        }
+
+        if self.len == self.de.config.max_members {
+            return Err(Error::TooManyMembers);
+        }
+        self.len += 1;
+
+        let start = self.de.index;
+        let key = seed.deserialize(MapKey { de: &mut *self.de })?;
+        self.key = if self.de.is_quote(peek) {
+            (start + 1, self.de.index - 1)
+        } else {
+            (start, self.de.index)
+        };
+
+        if let Some(keys) = self.keys.as_mut() {
+            keys.insert(self.de.slice, 0, self.key.0, self.key.1)?;
         }
+
+        Ok(Some(key))
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
     where
         V: de::DeserializeSeed<'de>,
     {
+        #[cfg(feature = "error-path")]
+        self.de.path.push_key(self.key.0, self.key.1);
+
         self.de.parse_object_colon()?;
+        let value = seed.deserialize(&mut *self.de)?;
+
+        #[cfg(feature = "error-path")]
+        self.de.path.pop();
 
-        seed.deserialize(&mut *self.de)
+        Ok(value)
     }
 }
 
@@ -64,137 +109,142 @@ struct MapKey<'a, 'b> {
 impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
     type Error = Error;
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Error>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        self.deserialize_str(visitor)
     }
 
     fn deserialize_bool<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_i8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_i16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_i32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_i64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_u8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_u16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_u32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_u64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_f32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_f64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.de.deserialize_str(visitor)
+        match self.de.parse_whitespace() {
+            Some(c) if self.de.is_key_start(c) && !self.de.is_quote(c) => {
+                visitor.visit_borrowed_str(self.de.parse_ident_key()?)
+            }
+            _ => self.de.deserialize_str(visitor),
+        }
     }
 
     fn deserialize_string<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_option<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_unit_struct<V>(
@@ -205,7 +255,7 @@ impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_newtype_struct<V>(
@@ -216,21 +266,21 @@ impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_tuple_struct<V>(
@@ -242,14 +292,14 @@ impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_struct<V>(
@@ -261,7 +311,7 @@ impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_enum<V>(
@@ -273,7 +323,7 @@ impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -5,20 +5,64 @@ use core::{fmt, str};
 
 use serde::de::{self, Visitor};
 
+use crate::Config;
+
 use self::enum_::UnitVariantAccess;
+use self::keys::KeySet;
 use self::map::MapAccess;
 use self::seq::SeqAccess;
+use self::skip::SkipChecks;
 
 mod enum_;
+mod format;
+mod framer;
+mod keys;
 mod map;
+#[cfg(feature = "error-path")]
+mod path;
+mod pointer;
+#[cfg(feature = "error-position")]
+mod position;
+mod reader;
 mod seq;
+mod skip;
+mod stream;
+mod tape;
+
+pub use self::format::{minify, minify_to, pretty_print};
+pub use self::framer::Framer;
+#[cfg(feature = "error-path")]
+pub use self::path::{Path, Segment};
+pub use self::pointer::{from_slice_at, pointer};
+#[cfg(feature = "error-position")]
+pub use self::position::{from_slice_with_position, from_str_with_position, PositionedError};
+pub use self::reader::{Reader, Token};
+pub use self::stream::StreamDeserializer;
+pub use self::tape::{parse_tape, Tape, TapeToken, TapeValue, TokenKind};
+
+/// How deeply values skipped by `deserialize_ignored_any` can be nested
+const SKIP_DEPTH_LIMIT: usize = 128;
 
 /// Deserialization result
 pub type Result<T> = core::result::Result<T, Error>;
 
 /// This type represents all possible errors that can occur when deserializing JSON data
 #[derive(Debug, PartialEq)]
+// `#[non_exhaustive]` is newer than our MSRV
+#[allow(clippy::manual_non_exhaustive)]
 pub enum Error {
+    /// A string contains a control character that isn't escaped (only in strict mode).
+    ControlCharacterInString,
+
+    /// The input is nested deeper than allowed.
+    DepthLimitExceeded,
+
+    /// An object has the same key more than once (only with `Config::deny_duplicate_keys`).
+    DuplicateKey,
+
+    /// A struct field appeared more than once.
+    DuplicateField(&'static str),
+
     /// EOF while parsing a list.
     EofWhileParsingList,
 
@@ -49,24 +93,72 @@ pub enum Error {
     /// Expected this character to start a JSON value.
     ExpectedSomeValue,
 
+    /// Sequence has the wrong number of elements.
+    InvalidLength(usize),
+
+    /// Invalid escape sequence in a string.
+    InvalidEscape,
+
     /// Invalid number.
     InvalidNumber,
 
+    /// The JSON pointer is not empty and doesn't start with `/`, or contains a `~` that isn't
+    /// followed by `0` or `1`.
+    InvalidPointer,
+
     /// Invalid type
     InvalidType,
 
     /// Invalid unicode code point.
     InvalidUnicodeCodePoint,
 
+    /// The value has the right type but is not one the `Deserialize` implementation accepts.
+    InvalidValue,
+
+    /// An object has too many keys to check them for duplicates (only with
+    /// `Config::deny_duplicate_keys`).
+    KeyLimitExceeded,
+
     /// Object key is not a string.
     KeyMustBeAString,
 
+    /// A required struct field is missing.
+    MissingField(&'static str),
+
+    /// A string or object key is longer than allowed.
+    StringTooLong,
+
+    /// The token array passed to `parse_tape` is too short for the document.
+    TapeFull,
+
+    /// An array has more elements than allowed.
+    TooManyElements,
+
+    /// An object has more members than allowed.
+    TooManyMembers,
+
+    /// The document has more values than the `value::Value` can hold.
+    TooManyNodes,
+
     /// JSON has non-whitespace trailing characters after the value.
     TrailingCharacters,
 
     /// JSON has a comma after the last value in an array or map.
     TrailingComma,
 
+    /// A struct field that is not expected (see `#[serde(deny_unknown_fields)]`), with its name
+    /// truncated to 32 bytes.
+    UnknownField(heapless::String<heapless::consts::U32>),
+
+    /// An enum variant that is not expected, with its name truncated to 32 bytes.
+    UnknownVariant(heapless::String<heapless::consts::U32>),
+
+    /// The requested type is not supported by this deserializer.
+    UnsupportedType,
+
+    /// The sink passed to `minify_to` or `pretty_print` failed.
+    WriteFailed,
+
     /// Error with a custom message that we had to discard.
     CustomError,
 
@@ -85,36 +177,110 @@ impl ::std::error::Error for Error {
     }
 }
 
-pub(crate) struct Deserializer<'b> {
+/// A structure that deserializes JSON text into Rust values
+pub struct Deserializer<'b> {
     slice: &'b [u8],
     index: usize,
+    config: Config,
+    /// Number of arrays and objects being deserialized
+    depth: usize,
+    #[cfg(feature = "error-path")]
+    path: self::path::PathStack,
 }
 
 impl<'a> Deserializer<'a> {
-    fn new(slice: &'a [u8]) -> Deserializer<'_> {
-        Deserializer { slice, index: 0 }
+    /// Creates a new JSON deserializer over `slice` with the default configuration
+    pub fn new(slice: &'a [u8]) -> Deserializer<'a> {
+        Deserializer::with_config(slice, Config::new())
+    }
+
+    /// Creates a new JSON deserializer over `slice` with the given configuration
+    pub fn with_config(slice: &'a [u8], config: Config) -> Deserializer<'a> {
+        Deserializer {
+            slice,
+            index: 0,
+            config,
+            depth: 0,
+            #[cfg(feature = "error-path")]
+            path: self::path::PathStack::new(),
+        }
+    }
+
+    /// Returns the number of bytes of the input consumed so far
+    pub fn offset(&self) -> usize {
+        self.index
     }
 
     fn eat_char(&mut self) {
         self.index += 1;
     }
 
-    fn end(&mut self) -> Result<()> {
+    /// Accounts for an array or object that is about to be deserialized
+    fn enter(&mut self) -> Result<()> {
+        if self.depth >= self.config.max_depth {
+            return Err(Error::DepthLimitExceeded);
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Checks the length of a string that spans `start..end` of the input
+    fn check_str_len(&self, start: usize, end: usize) -> Result<()> {
+        if end - start > self.config.max_string_len {
+            return Err(Error::StringTooLong);
+        }
+        Ok(())
+    }
+
+    /// Checks that the rest of the input is a single well-formed JSON value, surrounded by
+    /// whitespace
+    ///
+    /// The whole configuration applies: its limits, the strict and lenient modes and
+    /// `deny_duplicate_keys`. Like skipped values, the input can be nested at most 128 levels deep,
+    /// or 32 with `max_elements`, `max_members` or `deny_duplicate_keys`. On error, `offset`
+    /// returns the position at which the error was detected.
+    ///
+    /// ```
+    /// use serde_json_core::de::{Deserializer, Error};
+    /// use serde_json_core::Config;
+    ///
+    /// let mut de = Deserializer::with_config(br#"{"ssid": "home", "psk": "hunter2"}"#, Config::new().max_string_len(4));
+    /// assert_eq!(de.validate(), Err(Error::StringTooLong));
+    /// assert_eq!(de.offset(), 32);
+    /// ```
+    pub fn validate(&mut self) -> Result<()> {
+        self.skip_value()?;
+        self.end()
+    }
+
+    /// Checks that the input only contains whitespace after the last deserialized value
+    ///
+    /// This should be called once deserialization is complete.
+    pub fn end(&mut self) -> Result<()> {
         match self.parse_whitespace() {
             Some(_) => Err(Error::TrailingCharacters),
             None => Ok(()),
         }
     }
 
-    fn end_seq(&mut self) -> Result<()> {
+    /// Consumes the end of an array whose elements start at `start`
+    fn end_seq(&mut self, start: usize) -> Result<()> {
         match self.parse_whitespace().ok_or(Error::EofWhileParsingList)? {
             b']' => {
                 self.eat_char();
                 Ok(())
             }
+            // a trailing comma has to follow an element
+            b',' if self.config.lenient && self.only_whitespace_since(start) => {
+                Err(Error::ExpectedSomeValue)
+            }
             b',' => {
                 self.eat_char();
                 match self.parse_whitespace() {
+                    Some(b']') if self.config.lenient => {
+                        self.eat_char();
+                        Ok(())
+                    }
                     Some(b']') => Err(Error::TrailingComma),
                     _ => Err(Error::TrailingCharacters),
                 }
@@ -123,7 +289,8 @@ impl<'a> Deserializer<'a> {
         }
     }
 
-    fn end_map(&mut self) -> Result<()> {
+    /// Consumes the end of an object whose members start at `start`
+    fn end_map(&mut self, start: usize) -> Result<()> {
         match self
             .parse_whitespace()
             .ok_or(Error::EofWhileParsingObject)?
@@ -132,26 +299,39 @@ impl<'a> Deserializer<'a> {
                 self.eat_char();
                 Ok(())
             }
+            // a trailing comma has to follow a member
+            b',' if self.config.lenient && self.only_whitespace_since(start) => {
+                Err(Error::KeyMustBeAString)
+            }
+            b',' if self.config.lenient => {
+                self.eat_char();
+                match self.parse_whitespace() {
+                    Some(b'}') => {
+                        self.eat_char();
+                        Ok(())
+                    }
+                    _ => Err(Error::TrailingCharacters),
+                }
+            }
             b',' => Err(Error::TrailingComma),
             _ => Err(Error::TrailingCharacters),
         }
     }
 
-    fn next_char(&mut self) -> Option<u8> {
-        let ch = self.slice.get(self.index);
-
-        if ch.is_some() {
-            self.index += 1;
-        }
-
-        ch.cloned()
+    /// Returns whether there is only whitespace, and comments, between `start` and the current
+    /// position
+    fn only_whitespace_since(&self, start: usize) -> bool {
+        let mut de = Deserializer::with_config(&self.slice[..self.index], self.config);
+        de.index = start;
+        de.parse_whitespace().is_none()
     }
 
     fn parse_ident(&mut self, ident: &[u8]) -> Result<()> {
         for c in ident {
-            if Some(*c) != self.next_char() {
+            if Some(*c) != self.peek() {
                 return Err(Error::ExpectedSomeIdent);
             }
+            self.eat_char();
         }
 
         Ok(())
@@ -170,29 +350,466 @@ impl<'a> Deserializer<'a> {
         }
     }
 
-    fn parse_str(&mut self) -> Result<&'a str> {
+    /// Consumes the characters that can make up a JSON number and returns them
+    ///
+    /// In strict mode the number is checked against the JSON number grammar. In lenient mode the
+    /// number can also be a hexadecimal integer.
+    fn parse_number(&mut self) -> Result<&'a str> {
         let start = self.index;
+        if self.at_hex_number() {
+            if self.peek() == Some(b'-') {
+                self.eat_char();
+            }
+            // `0x`
+            self.index += 2;
+            let digits = self.index;
+            while let Some(c) = self.peek() {
+                if !c.is_ascii_hexdigit() {
+                    break;
+                }
+                self.eat_char();
+            }
+            if self.index == digits {
+                return Err(Error::InvalidNumber);
+            }
+        } else if self.config.strict {
+            self.skip_number()?;
+        } else {
+            while let Some(c) = self.peek() {
+                match c {
+                    b'0'..=b'9' | b'+' | b'-' | b'.' | b'e' | b'E' => self.eat_char(),
+                    _ => break,
+                }
+            }
+        }
+
+        Ok(unsafe {
+            // already checked that it contains only ascii
+            str::from_utf8_unchecked(&self.slice[start..self.index])
+        })
+    }
+
+    /// Returns whether `c` opens a string: `"`, or also `'` in lenient mode
+    fn is_quote(&self, c: u8) -> bool {
+        c == b'"' || (c == b'\'' && self.config.lenient)
+    }
+
+    /// Returns whether `c` starts an object key: a quote, or also an identifier in lenient mode
+    fn is_key_start(&self, c: u8) -> bool {
+        self.is_quote(c) || (self.config.lenient && is_ident_start(c))
+    }
+
+    /// Consumes an unquoted object key, checking its length like that of a string
+    fn parse_ident_key(&mut self) -> Result<&'a str> {
+        let start = self.index;
+        while let Some(c) = self.peek() {
+            if is_ident_start(c) || c.is_ascii_digit() {
+                self.eat_char();
+            } else {
+                break;
+            }
+        }
+        self.check_str_len(start, self.index)?;
+
+        Ok(unsafe {
+            // already checked that it contains only ascii
+            str::from_utf8_unchecked(&self.slice[start..self.index])
+        })
+    }
+
+    /// Consumes the rest of a string after its opening `quote` and returns it, escape sequences
+    /// included
+    fn parse_str(&mut self, quote: u8) -> Result<&'a str> {
+        let start = self.index;
+        if self.config.strict {
+            self.skip_str(quote)?;
+            return Ok(unsafe {
+                // `skip_str` checked that it's valid UTF-8
+                str::from_utf8_unchecked(&self.slice[start..self.index - 1])
+            });
+        }
+
         loop {
             match self.peek() {
-                Some(b'"') => {
+                Some(c) if c == quote => {
                     let end = self.index;
+                    self.check_str_len(start, end)?;
                     self.eat_char();
                     return str::from_utf8(&self.slice[start..end])
                         .map_err(|_| Error::InvalidUnicodeCodePoint);
                 }
+                Some(b'\\') => {
+                    // leave the escape sequence as is, but don't let an escaped quote end the string
+                    self.eat_char();
+                    self.peek().ok_or(Error::EofWhileParsingString)?;
+                    self.eat_char();
+                }
                 Some(_) => self.eat_char(),
                 None => return Err(Error::EofWhileParsingString),
             }
         }
     }
 
-    /// Consumes all the whitespace characters and returns a peek into the next character
+    /// In strict mode, rejects a digit that follows a leading `0`
+    fn check_leading_zero(&mut self) -> Result<()> {
+        match self.peek() {
+            Some(b'0'..=b'9') if self.config.strict => Err(Error::InvalidNumber),
+            _ => Ok(()),
+        }
+    }
+
+    /// In lenient mode, consumes the `x` of a hexadecimal number's `0x` prefix
+    fn parse_hex_prefix(&mut self) -> bool {
+        match self.peek() {
+            Some(b'x') | Some(b'X') if self.config.lenient => {
+                self.eat_char();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Consumes a digit in the given `radix` and returns its value
+    fn parse_digit(&mut self, radix: u8) -> Option<u8> {
+        let digit = (self.peek()? as char).to_digit(u32::from(radix))?;
+        self.eat_char();
+        Some(digit as u8)
+    }
+
+    /// Returns whether the next value is a hexadecimal integer, like `0x1F` or `-0x1F`
+    fn at_hex_number(&self) -> bool {
+        let rest = &self.slice[self.index..];
+        let rest = if rest.first() == Some(&b'-') {
+            &rest[1..]
+        } else {
+            rest
+        };
+
+        self.config.lenient
+            && rest.len() > 1
+            && rest[0] == b'0'
+            && (rest[1] == b'x' || rest[1] == b'X')
+    }
+
+    /// Consumes one or more digits
+    fn parse_digits(&mut self) -> Result<()> {
+        match self.peek() {
+            Some(b'0'..=b'9') => self.eat_char(),
+            Some(_) => return Err(Error::InvalidNumber),
+            None => return Err(Error::EofWhileParsingNumber),
+        }
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.eat_char();
+        }
+
+        Ok(())
+    }
+
+    /// Consumes a number, checking that it follows the JSON number grammar
+    fn skip_number(&mut self) -> Result<()> {
+        if self.peek() == Some(b'-') {
+            self.eat_char();
+        }
+        match self.peek() {
+            Some(b'0') => {
+                self.eat_char();
+                if self.parse_hex_prefix() {
+                    self.parse_digit(16).ok_or(Error::InvalidNumber)?;
+                    while self.parse_digit(16).is_some() {}
+                    return Ok(());
+                }
+                self.check_leading_zero()?;
+            }
+            _ => self.parse_digits()?,
+        }
+        if self.peek() == Some(b'.') {
+            self.eat_char();
+            self.parse_digits()?;
+        }
+        match self.peek() {
+            Some(b'e') | Some(b'E') => {
+                self.eat_char();
+                match self.peek() {
+                    Some(b'+') | Some(b'-') => self.eat_char(),
+                    _ => {}
+                }
+                self.parse_digits()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Consumes a number and returns its text, checking that it follows the JSON number grammar
+    /// and isn't followed by more number characters, like the `-2` of `1-2`
+    fn parse_checked_number(&mut self) -> Result<&'a str> {
+        let start = self.index;
+        self.skip_number()?;
+        match self.peek() {
+            Some(b'0'..=b'9') | Some(b'+') | Some(b'-') | Some(b'.') | Some(b'e') | Some(b'E') => {
+                Err(Error::InvalidNumber)
+            }
+            _ => Ok(unsafe {
+                // already checked that it contains only ascii
+                str::from_utf8_unchecked(&self.slice[start..self.index])
+            }),
+        }
+    }
+
+    /// Consumes the rest of a string after its opening `quote`, checking its escape sequences and
+    /// encoding
+    fn skip_str(&mut self, quote: u8) -> Result<()> {
+        let start = self.index;
+        loop {
+            match self.peek().ok_or(Error::EofWhileParsingString)? {
+                c if c == quote => break,
+                b'\\' => {
+                    self.eat_char();
+                    match self.peek().ok_or(Error::EofWhileParsingString)? {
+                        b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => self.eat_char(),
+                        b'\'' if self.config.lenient => self.eat_char(),
+                        b'u' => {
+                            self.eat_char();
+                            for _ in 0..4 {
+                                match self.peek().ok_or(Error::EofWhileParsingString)? {
+                                    b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F' => self.eat_char(),
+                                    _ => return Err(Error::InvalidEscape),
+                                }
+                            }
+                        }
+                        _ => return Err(Error::InvalidEscape),
+                    }
+                }
+                0x00..=0x1f if self.config.strict => return Err(Error::ControlCharacterInString),
+                _ => self.eat_char(),
+            }
+        }
+
+        let end = self.index;
+        self.check_str_len(start, end)?;
+        self.eat_char();
+        str::from_utf8(&self.slice[start..end])
+            .map(|_| ())
+            .map_err(|_| Error::InvalidUnicodeCodePoint)
+    }
+
+    /// Consumes an object key and returns its byte range, without its quotes
+    fn skip_key(&mut self) -> Result<(usize, usize)> {
+        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+        let start = self.index;
+        let key = match peek {
+            c if self.is_quote(c) => {
+                self.eat_char();
+                self.skip_str(c)?;
+                (start + 1, self.index - 1)
+            }
+            c if self.is_key_start(c) => {
+                self.parse_ident_key()?;
+                (start, self.index)
+            }
+            b'}' => return Err(Error::TrailingComma),
+            _ => return Err(Error::KeyMustBeAString),
+        };
+        Ok(key)
+    }
+
+    /// Skips over the next value, checking that it's valid JSON
+    ///
+    /// The configured `max_elements` and `max_members` apply, and so does `deny_duplicate_keys`.
+    fn skip_value(&mut self) -> Result<()> {
+        if self.config.limits_skipped_values() {
+            self.skip_checked_value()
+        } else {
+            self.skip_value_with(None)
+        }
+    }
+
+    /// Skips over the next value, counting elements and members and looking for duplicate keys
+    ///
+    /// The `SkipChecks` live in this function's stack frame, so that they're only reserved when
+    /// they're needed.
+    #[inline(never)]
+    fn skip_checked_value(&mut self) -> Result<()> {
+        let mut checks = SkipChecks::new();
+        self.skip_value_with(Some(&mut checks))
+    }
+
+    /// Skips over the next value, applying the `checks` if there are any
+    ///
+    /// This doesn't recurse: the kinds of the open containers are kept in a fixed-size bit set,
+    /// so nesting deeper than `SKIP_DEPTH_LIMIT` is reported as `Error::DepthLimitExceeded`, like
+    /// nesting deeper than the configured `max_depth`.
+    fn skip_value_with(&mut self, mut checks: Option<&mut SkipChecks>) -> Result<()> {
+        // bit `n` is set if the `n`-th open container is an object
+        let mut objects = [0u8; SKIP_DEPTH_LIMIT / 8];
+        let mut depth = 0;
+
+        loop {
+            match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+                c @ b'[' | c @ b'{' => {
+                    let object = c == b'{';
+                    let (close, eof) = if object {
+                        (b'}', Error::EofWhileParsingObject)
+                    } else {
+                        (b']', Error::EofWhileParsingList)
+                    };
+
+                    if self.depth + depth >= self.config.max_depth {
+                        return Err(Error::DepthLimitExceeded);
+                    }
+
+                    self.eat_char();
+                    if self.parse_whitespace().ok_or(eof)? != close {
+                        if depth == SKIP_DEPTH_LIMIT {
+                            return Err(Error::DepthLimitExceeded);
+                        }
+                        if let Some(checks) = checks.as_mut() {
+                            checks.open()?;
+                        }
+                        if object {
+                            objects[depth / 8] |= 1 << (depth % 8);
+                            self.skip_member(&mut checks)?;
+                        } else {
+                            objects[depth / 8] &= !(1 << (depth % 8));
+                            if let Some(checks) = checks.as_mut() {
+                                checks.element(&self.config)?;
+                            }
+                        }
+                        depth += 1;
+                        continue;
+                    }
+                    self.eat_char();
+                }
+                c if self.is_quote(c) => {
+                    self.eat_char();
+                    self.skip_str(c)?;
+                }
+                b'n' => self.parse_ident(b"null")?,
+                b't' => self.parse_ident(b"true")?,
+                b'f' => self.parse_ident(b"false")?,
+                b'-' | b'0'..=b'9' => {
+                    self.parse_checked_number()?;
+                }
+                _ => return Err(Error::ExpectedSomeValue),
+            }
+
+            // a value is complete; close containers until another value is due
+            loop {
+                if depth == 0 {
+                    return Ok(());
+                }
+
+                let object = objects[(depth - 1) / 8] & (1 << ((depth - 1) % 8)) != 0;
+                if object {
+                    match self
+                        .parse_whitespace()
+                        .ok_or(Error::EofWhileParsingObject)?
+                    {
+                        b',' => {
+                            self.eat_char();
+                            if self.config.lenient && self.parse_whitespace() == Some(b'}') {
+                                self.eat_char();
+                            } else {
+                                self.skip_member(&mut checks)?;
+                                break;
+                            }
+                        }
+                        b'}' => self.eat_char(),
+                        _ => return Err(Error::ExpectedObjectCommaOrEnd),
+                    }
+                } else {
+                    match self.parse_whitespace().ok_or(Error::EofWhileParsingList)? {
+                        b',' => {
+                            self.eat_char();
+                            if self.parse_whitespace() != Some(b']') {
+                                if let Some(checks) = checks.as_mut() {
+                                    checks.element(&self.config)?;
+                                }
+                                break;
+                            }
+                            if !self.config.lenient {
+                                return Err(Error::TrailingComma);
+                            }
+                            self.eat_char();
+                        }
+                        b']' => self.eat_char(),
+                        _ => return Err(Error::ExpectedListCommaOrEnd),
+                    }
+                }
+                if let Some(checks) = checks.as_mut() {
+                    checks.close();
+                }
+                depth -= 1;
+            }
+        }
+    }
+
+    /// Skips over the key of an object member and its colon, counting the member in `checks`
+    fn skip_member(&mut self, checks: &mut Option<&mut SkipChecks>) -> Result<()> {
+        let (start, end) = self.skip_key()?;
+        if let Some(checks) = checks.as_mut() {
+            checks.member(&self.config, self.slice, start, end)?;
+        }
+        self.parse_object_colon()
+    }
+
+    /// Skips over the next value and returns its JSON text
+    fn parse_raw(&mut self) -> Result<&'a str> {
+        self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+        let start = self.index;
+        self.skip_value()?;
+
+        str::from_utf8(&self.slice[start..self.index]).map_err(|_| Error::InvalidUnicodeCodePoint)
+    }
+
+    /// Deserializes an object into a map or a struct
+    fn deserialize_object<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'a>,
+    {
+        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+
+        if peek == b'{' {
+            self.enter()?;
+            self.eat_char();
+            let start = self.index;
+
+            let ret = if self.config.deny_duplicate_keys {
+                self.visit_unique_map(visitor)?
+            } else {
+                visitor.visit_map(MapAccess::new(self, None))?
+            };
+
+            self.end_map(start)?;
+            self.depth -= 1;
+
+            Ok(ret)
+        } else {
+            Err(Error::InvalidType)
+        }
+    }
+
+    /// Visits the members of an object, checking that its keys are unique
+    ///
+    /// The set of keys lives in this function's stack frame, so that it's only reserved when
+    /// duplicate keys are denied.
+    #[inline(never)]
+    fn visit_unique_map<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'a>,
+    {
+        let mut keys = KeySet::new();
+        visitor.visit_map(MapAccess::new(self, Some(&mut keys)))
+    }
+
+    /// Consumes all the whitespace characters, and comments in lenient mode, and returns a peek
+    /// into the next character
     fn parse_whitespace(&mut self) -> Option<u8> {
         loop {
             match self.peek() {
                 Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') => {
                     self.eat_char();
                 }
+                Some(b'/') if self.config.lenient && self.skip_comment() => {}
                 other => {
                     return other;
                 }
@@ -200,11 +817,39 @@ impl<'a> Deserializer<'a> {
         }
     }
 
+    /// Consumes a `//` or `/* */` comment, if one starts at the current position
+    ///
+    /// A `//` comment can end at the end of the input. A `/*` comment that isn't closed is not
+    /// consumed, so that it's reported as an unexpected character.
+    fn skip_comment(&mut self) -> bool {
+        let rest = &self.slice[self.index..];
+        let len = match rest.get(1) {
+            Some(b'/') => rest
+                .iter()
+                .position(|&c| c == b'\n')
+                .map(|i| i + 1)
+                .unwrap_or_else(|| rest.len()),
+            Some(b'*') => match rest[2..].windows(2).position(|w| w == b"*/") {
+                Some(i) => i + 4,
+                None => return false,
+            },
+            _ => return false,
+        };
+
+        self.index += len;
+        true
+    }
+
     fn peek(&mut self) -> Option<u8> {
         self.slice.get(self.index).cloned()
     }
 }
 
+/// Returns whether `c` can start an unquoted object key in lenient mode
+fn is_ident_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_' || c == b'$'
+}
+
 // NOTE(deserialize_*signed) we avoid parsing into u64 and then casting to a smaller integer, which
 // is what upstream does, to avoid pulling in 64-bit compiler intrinsics, which waste a few KBs of
 // Flash, when targeting non 64-bit architectures
@@ -214,32 +859,32 @@ macro_rules! deserialize_unsigned {
             .parse_whitespace()
             .ok_or(Error::EofWhileParsingValue)?;
 
-        match peek {
-            b'-' => Err(Error::InvalidNumber),
+        let (radix, first) = match peek {
+            b'-' => return Err(Error::InvalidNumber),
             b'0' => {
                 $self.eat_char();
-                $visitor.$visit_uxx(0)
+                if !$self.parse_hex_prefix() {
+                    $self.check_leading_zero()?;
+                    return $visitor.$visit_uxx(0);
+                }
+                (16, $self.parse_digit(16).ok_or(Error::InvalidNumber)?)
             }
             b'1'..=b'9' => {
                 $self.eat_char();
-
-                let mut number = (peek - b'0') as $uxx;
-                loop {
-                    match $self.peek() {
-                        Some(c @ b'0'..=b'9') => {
-                            $self.eat_char();
-                            number = number
-                                .checked_mul(10)
-                                .ok_or(Error::InvalidNumber)?
-                                .checked_add((c - b'0') as $uxx)
-                                .ok_or(Error::InvalidNumber)?;
-                        }
-                        _ => return $visitor.$visit_uxx(number),
-                    }
-                }
+                (10, peek - b'0')
             }
-            _ => Err(Error::InvalidType),
+            _ => return Err(Error::InvalidType),
+        };
+
+        let mut number = first as $uxx;
+        while let Some(digit) = $self.parse_digit(radix) {
+            number = number
+                .checked_mul(radix as $uxx)
+                .ok_or(Error::InvalidNumber)?
+                .checked_add(digit as $uxx)
+                .ok_or(Error::InvalidNumber)?;
         }
+        $visitor.$visit_uxx(number)
     }};
 }
 
@@ -256,66 +901,85 @@ macro_rules! deserialize_signed {
             _ => false,
         };
 
-        match $self.peek().ok_or(Error::EofWhileParsingValue)? {
+        let (radix, first) = match $self.peek().ok_or(Error::EofWhileParsingValue)? {
             b'0' => {
                 $self.eat_char();
-                $visitor.$visit_ixx(0)
+                if !$self.parse_hex_prefix() {
+                    $self.check_leading_zero()?;
+                    return $visitor.$visit_ixx(0);
+                }
+                (16, $self.parse_digit(16).ok_or(Error::InvalidNumber)?)
             }
             c @ b'1'..=b'9' => {
                 $self.eat_char();
-
-                let mut number = (c - b'0') as $ixx * if signed { -1 } else { 1 };
-                loop {
-                    match $self.peek() {
-                        Some(c @ b'0'..=b'9') => {
-                            $self.eat_char();
-                            number = number
-                                .checked_mul(10)
-                                .ok_or(Error::InvalidNumber)?
-                                .checked_add((c - b'0') as $ixx * if signed { -1 } else { 1 })
-                                .ok_or(Error::InvalidNumber)?;
-                        }
-                        _ => return $visitor.$visit_ixx(number),
-                    }
-                }
+                (10, c - b'0')
             }
             _ => return Err(Error::InvalidType),
+        };
+
+        let sign = if signed { -1 } else { 1 };
+        let mut number = first as $ixx * sign;
+        while let Some(digit) = $self.parse_digit(radix) {
+            number = number
+                .checked_mul(radix as $ixx)
+                .ok_or(Error::InvalidNumber)?
+                .checked_add(digit as $ixx * sign)
+                .ok_or(Error::InvalidNumber)?;
         }
+        $visitor.$visit_ixx(number)
     }};
 }
 
 macro_rules! deserialize_fromstr {
-    ($self:ident, $visitor:ident, $typ:ident, $visit_fn:ident, $pattern:expr) => {{
-        let start = $self.index;
-        loop {
-            match $self.peek() {
-                Some(c) => {
-                    if $pattern.iter().find(|&&d| d == c).is_some() {
-                        $self.eat_char();
-                    } else {
-                        let s = unsafe {
-                            // already checked that it contains only ascii
-                            str::from_utf8_unchecked(&$self.slice[start..$self.index])
-                        };
-                        let v = $typ::from_str(s).or(Err(Error::InvalidNumber))?;
-                        return $visitor.$visit_fn(v);
-                    }
-                }
-                None => return Err(Error::EofWhileParsingNumber),
-            }
-        }
+    ($self:ident, $visitor:ident, $typ:ident, $visit_fn:ident) => {{
+        let s = $self.parse_number()?;
+        let v = $typ::from_str(s).or(Err(Error::InvalidNumber))?;
+        $visitor.$visit_fn(v)
     }};
 }
 
-impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     type Error = Error;
 
-    /// Unsupported. Can’t parse a value without knowing its expected type.
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    /// Numbers are visited as `u64`, `i64` or, if they have a fraction or an exponent, `f64`.
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+            b'n' => {
+                self.eat_char();
+                self.parse_ident(b"ull")?;
+                visitor.visit_unit()
+            }
+            b't' | b'f' => self.deserialize_bool(visitor),
+            c if self.is_quote(c) => self.deserialize_str(visitor),
+            b'[' => self.deserialize_seq(visitor),
+            b'{' => self.deserialize_map(visitor),
+            b'-' | b'0'..=b'9' if self.at_hex_number() => {
+                if self.peek() == Some(b'-') {
+                    self.deserialize_i64(visitor)
+                } else {
+                    self.deserialize_u64(visitor)
+                }
+            }
+            b'-' | b'0'..=b'9' => {
+                let s = self.parse_number()?;
+                if !s.bytes().any(|c| c == b'.' || c == b'e' || c == b'E') {
+                    if s.starts_with('-') {
+                        if let Ok(n) = i64::from_str(s) {
+                            return visitor.visit_i64(n);
+                        }
+                    } else if let Ok(n) = u64::from_str(s) {
+                        return visitor.visit_u64(n);
+                    }
+                }
+
+                // numbers with a fraction or an exponent, and integers too large for 64 bits
+                visitor.visit_f64(f64::from_str(s).or(Err(Error::InvalidNumber))?)
+            }
+            _ => Err(Error::ExpectedSomeValue),
+        }
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
@@ -400,7 +1064,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         V: Visitor<'de>,
     {
         self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
-        deserialize_fromstr!(self, visitor, f32, visit_f32, b"0123456789+-.eE")
+        deserialize_fromstr!(self, visitor, f32, visit_f32)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
@@ -408,14 +1072,14 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         V: Visitor<'de>,
     {
         self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
-        deserialize_fromstr!(self, visitor, f64, visit_f64, b"0123456789+-.eE")
+        deserialize_fromstr!(self, visitor, f64, visit_f64)
     }
 
     fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
@@ -425,9 +1089,9 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
 
         match peek {
-            b'"' => {
+            c if self.is_quote(c) => {
                 self.eat_char();
-                visitor.visit_borrowed_str(self.parse_str()?)
+                visitor.visit_borrowed_str(self.parse_str(c)?)
             }
             _ => Err(Error::InvalidType),
         }
@@ -438,7 +1102,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     /// Unsupported
@@ -446,7 +1110,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     /// Unsupported
@@ -454,7 +1118,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
@@ -476,7 +1140,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     /// Unsupported. Use a more specific deserialize_* method
@@ -484,27 +1148,35 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
-    /// Unsupported. We can’t parse newtypes because we don’t know the underlying type.
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value>
+    /// Unsupported, except for `value::RawValue`. We can’t parse newtypes because we don’t know
+    /// the underlying type.
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        if name == crate::value::RAW_VALUE_TOKEN {
+            visitor.visit_borrowed_str(self.parse_raw()?)
+        } else {
+            Err(Error::UnsupportedType)
+        }
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.peek().ok_or(Error::EofWhileParsingValue)? {
+        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
             b'[' => {
+                self.enter()?;
                 self.eat_char();
+                let start = self.index;
                 let ret = visitor.visit_seq(SeqAccess::new(self))?;
 
-                self.end_seq()?;
+                self.end_seq(start)?;
+                self.depth -= 1;
 
                 Ok(ret)
             }
@@ -535,19 +1207,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
-
-        if peek == b'{' {
-            self.eat_char();
-
-            let ret = visitor.visit_map(MapAccess::new(self))?;
-
-            self.end_map()?;
-
-            Ok(ret)
-        } else {
-            Err(Error::InvalidType)
-        }
+        self.deserialize_object(visitor)
     }
 
     fn deserialize_struct<V>(
@@ -559,7 +1219,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_map(visitor)
+        self.deserialize_object(visitor)
     }
 
     fn deserialize_enum<V>(
@@ -572,7 +1232,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         V: Visitor<'de>,
     {
         match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
-            b'"' => visitor.visit_enum(UnitVariantAccess::new(self)),
+            c if self.is_quote(c) => visitor.visit_enum(UnitVariantAccess::new(self)),
             _ => Err(Error::ExpectedSomeValue),
         }
     }
@@ -585,29 +1245,19 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     }
 
     /// Used to throw out fields from JSON objects that we don’t want to
-    /// keep in our structs.
+    /// keep in our structs. The skipped value is checked to be valid JSON.
+    ///
+    /// Nested containers are skipped without recursion, so this uses a small, constant amount
+    /// of stack.
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
-            b'"' => self.deserialize_str(visitor),
-            b'[' => self.deserialize_seq(visitor),
-            b'{' => self.deserialize_struct("ignored", &[], visitor),
-            b',' | b'}' | b']' => Err(Error::ExpectedSomeValue),
-            // If it’s something else then we chomp until we get to an end delimiter.
-            // This does technically allow for illegal JSON since we’re just ignoring
-            // characters rather than parsing them.
-            _ => loop {
-                match self.peek() {
-                    // The visitor is expected to be UnknownAny’s visitor, which
-                    // implements visit_unit to return its unit Ok result.
-                    Some(b',') | Some(b'}') | Some(b']') => break visitor.visit_unit(),
-                    Some(_) => self.eat_char(),
-                    None => break Err(Error::EofWhileParsingString),
-                }
-            },
-        }
+        self.skip_value()?;
+
+        // The visitor is expected to be IgnoredAny’s visitor, which
+        // implements visit_unit to return its unit Ok result.
+        visitor.visit_unit()
     }
 }
 
@@ -625,19 +1275,88 @@ impl de::Error for Error {
         {
             use core::fmt::Write;
 
-            let mut string = heapless::String::new();
-            write!(string, "{:.64}", msg).unwrap();
-            Error::CustomErrorWithMessage(string)
+            let mut string = heapless::String::new();
+            // NOTE `fmt::Arguments` and other `Display` implementations that ignore the precision
+            // may not fit in `string`; in that case we keep whatever was written before it filled up
+            write!(string, "{:.64}", msg).ok();
+            Error::CustomErrorWithMessage(string)
+        }
+    }
+
+    // with `custom-error-messages`, wrong types, values and lengths keep serde's message by going
+    // through `custom`, like serde's default implementations of these methods do
+    #[cfg(not(feature = "custom-error-messages"))]
+    fn invalid_type(_unexp: de::Unexpected<'_>, _exp: &dyn de::Expected) -> Self {
+        Error::InvalidType
+    }
+
+    #[cfg_attr(not(feature = "custom-error-messages"), allow(unused_variables))]
+    fn invalid_value(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        if let de::Unexpected::Other(crate::value::TOO_MANY_NODES) = unexp {
+            return Error::TooManyNodes;
+        }
+
+        #[cfg(not(feature = "custom-error-messages"))]
+        {
+            Error::InvalidValue
+        }
+        #[cfg(feature = "custom-error-messages")]
+        {
+            de::Error::custom(format_args!("invalid value: {}, expected {}", unexp, exp))
+        }
+    }
+
+    #[cfg(not(feature = "custom-error-messages"))]
+    fn invalid_length(len: usize, _exp: &dyn de::Expected) -> Self {
+        Error::InvalidLength(len)
+    }
+
+    fn unknown_variant(variant: &str, _expected: &'static [&'static str]) -> Self {
+        Error::UnknownVariant(truncate(variant))
+    }
+
+    fn unknown_field(field: &str, _expected: &'static [&'static str]) -> Self {
+        Error::UnknownField(truncate(field))
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::MissingField(field)
+    }
+
+    fn duplicate_field(field: &'static str) -> Self {
+        Error::DuplicateField(field)
+    }
+}
+
+/// Copies as much of `name` as fits into an error
+fn truncate(name: &str) -> heapless::String<heapless::consts::U32> {
+    let mut string = heapless::String::new();
+    for c in name.chars() {
+        if string.push(c).is_err() {
+            break;
         }
     }
+    string
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DuplicateField(field) => return write!(f, "Duplicate field `{}`.", field),
+            Error::InvalidLength(len) => return write!(f, "Invalid length {}.", len),
+            Error::MissingField(field) => return write!(f, "Missing field `{}`.", field),
+            Error::UnknownField(field) => return write!(f, "Unknown field `{}`.", field),
+            Error::UnknownVariant(variant) => return write!(f, "Unknown variant `{}`.", variant),
+            _ => {}
+        }
+
         write!(
             f,
             "{}",
             match self {
+                Error::ControlCharacterInString => "Unescaped control character in a string.",
+                Error::DepthLimitExceeded => "The input is nested too deeply.",
+                Error::DuplicateKey => "The object has a duplicate key.",
                 Error::EofWhileParsingList => "EOF while parsing a list.",
                 Error::EofWhileParsingObject => "EOF while parsing an object.",
                 Error::EofWhileParsingString => "EOF while parsing a string.",
@@ -658,16 +1377,27 @@ impl fmt::Display for Error {
                      `null`."
                 }
                 Error::ExpectedSomeValue => "Expected this character to start a JSON value.",
+                Error::InvalidEscape => "Invalid escape sequence in a string.",
                 Error::InvalidNumber => "Invalid number.",
+                Error::InvalidPointer => "Invalid JSON pointer.",
                 Error::InvalidType => "Invalid type",
                 Error::InvalidUnicodeCodePoint => "Invalid unicode code point.",
+                Error::InvalidValue => "Invalid value.",
+                Error::KeyLimitExceeded => "The object has too many keys to check for duplicates.",
                 Error::KeyMustBeAString => "Object key is not a string.",
+                Error::StringTooLong => "The string is too long.",
+                Error::TapeFull => "The document has more tokens than the tape can hold.",
+                Error::TooManyElements => "The array has too many elements.",
+                Error::TooManyMembers => "The object has too many members.",
+                Error::TooManyNodes => "The document has more values than the `Value` can hold.",
                 Error::TrailingCharacters => {
                     "JSON has non-whitespace trailing characters after \
                      the \
                      value."
                 }
                 Error::TrailingComma => "JSON has a comma after the last value in an array or map.",
+                Error::UnsupportedType => "Unsupported type.",
+                Error::WriteFailed => "Writing the output failed.",
                 Error::CustomError => "JSON does not match deserializer’s expected format.",
                 #[cfg(feature = "custom-error-messages")]
                 Error::CustomErrorWithMessage(msg) => msg.as_str(),
@@ -689,6 +1419,27 @@ where
     Ok(value)
 }
 
+/// Deserializes an instance of type `T` from the start of `v`, ignoring any bytes that follow it
+///
+/// Returns the value along with the number of bytes it took up, so that the caller can discard
+/// them. Whitespace after the value is not counted.
+///
+/// ```
+/// let buf = b"[1, 2] [3";
+/// let (value, n) = serde_json_core::de::from_slice_prefix::<[u8; 2]>(buf).unwrap();
+/// assert_eq!(value, [1, 2]);
+/// assert_eq!(&buf[n..], b" [3");
+/// ```
+pub fn from_slice_prefix<'a, T>(v: &'a [u8]) -> Result<(T, usize)>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v);
+    let value = de::Deserialize::deserialize(&mut de)?;
+
+    Ok((value, de.offset()))
+}
+
 /// Deserializes an instance of type T from a string of JSON text
 pub fn from_str<'a, T>(s: &'a str) -> Result<T>
 where
@@ -697,10 +1448,36 @@ where
     from_slice(s.as_bytes())
 }
 
+/// Checks that `v` is a single well-formed JSON value, surrounded by whitespace, without
+/// deserializing it
+///
+/// Use `Deserializer::validate` to apply limits or to find where the input is malformed.
+///
+/// ```
+/// use serde_json_core::de::{validate, Error};
+///
+/// assert_eq!(validate(br#"{"led": [true, false]}"#), Ok(()));
+/// assert_eq!(validate(br#"{"led": [true, false}"#), Err(Error::ExpectedListCommaOrEnd));
+/// ```
+pub fn validate(v: &[u8]) -> Result<()> {
+    Deserializer::new(v).validate()
+}
+
 #[cfg(test)]
 mod tests {
     use serde_derive::Deserialize;
 
+    /// Deserializes all of `input` as a `T` with the given configuration
+    fn parse_with<'a, T>(input: &'a str, config: crate::Config) -> crate::de::Result<T>
+    where
+        T: serde::Deserialize<'a>,
+    {
+        let mut de = crate::de::Deserializer::with_config(input.as_bytes(), config);
+        let value = T::deserialize(&mut de)?;
+        de.end()?;
+        Ok(value)
+    }
+
     #[derive(Debug, Deserialize, PartialEq)]
     enum Type {
         #[serde(rename = "boolean")]
@@ -716,8 +1493,11 @@ mod tests {
         assert_eq!(crate::from_str::<[i32; 0]>("[]"), Ok([]));
         assert_eq!(crate::from_str("[0, 1, 2]"), Ok([0, 1, 2]));
 
+        assert_eq!(crate::from_str(" [0, 1]"), Ok([0, 1]));
+
         // errors
         assert!(crate::from_str::<[i32; 2]>("[0, 1,]").is_err());
+        assert!(crate::from_str::<[i32; 1]>("[, 0]").is_err());
     }
 
     #[test]
@@ -831,6 +1611,10 @@ mod tests {
         assert!(crate::from_str::<Temperature>(r#"{ "temperature": 1 1 }"#).is_err());
         assert!(crate::from_str::<Temperature>(r#"{ "temperature": 0.0. }"#).is_err());
         assert!(crate::from_str::<Temperature>(r#"{ "temperature": ä }"#).is_err());
+
+        // top-level
+        assert_eq!(crate::from_str("-2.5"), Ok(-2.5f32));
+        assert_eq!(crate::from_str("1e3"), Ok(1000f64));
     }
 
     #[test]
@@ -890,7 +1674,7 @@ mod tests {
         // wrong number of args
         assert_eq!(
             crate::from_str::<Xy>(r#"[10]"#),
-            Err(crate::de::Error::CustomError)
+            Err(crate::de::Error::InvalidLength(1))
         );
         assert_eq!(
             crate::from_str::<Xy>(r#"[10, 20, 30]"#),
@@ -918,6 +1702,67 @@ mod tests {
             crate::from_str::<Xy>(r#"[10, 20, 30]"#),
             Err(crate::de::Error::TrailingCharacters)
         );
+
+        // messages longer than 64 bytes are cut short instead of panicking
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct TemperatureAndHumidityReadingFromTheOutdoorSensor(i8, i8);
+
+        assert_eq!(
+            crate::from_str::<TemperatureAndHumidityReadingFromTheOutdoorSensor>("[1]"),
+            Err(crate::de::Error::CustomErrorWithMessage(
+                "invalid length 1, expected ".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn prefix() {
+        use crate::de::{from_slice_prefix, Error};
+
+        assert_eq!(from_slice_prefix::<bool>(b"true"), Ok((true, 4)));
+        assert_eq!(from_slice_prefix::<bool>(b"  true\nfal"), Ok((true, 6)));
+        assert_eq!(
+            from_slice_prefix::<&str>(br#""hello"{"next":"#),
+            Ok(("hello", 7))
+        );
+        assert_eq!(
+            from_slice_prefix::<[u8; 2]>(b"[1, 2"),
+            Err(Error::EofWhileParsingList)
+        );
+    }
+
+    #[test]
+    fn structured_errors() {
+        use crate::de::Error;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        struct Led {
+            led: bool,
+        }
+
+        assert_eq!(
+            crate::from_str::<Led>(r#"{}"#),
+            Err(Error::MissingField("led"))
+        );
+        assert_eq!(
+            crate::from_str::<Led>(r#"{ "led": true, "led": false }"#),
+            Err(Error::DuplicateField("led"))
+        );
+        assert_eq!(
+            crate::from_str::<Led>(r#"{ "led": true, "color": "red" }"#),
+            Err(Error::UnknownField("color".into()))
+        );
+        assert_eq!(
+            crate::from_str::<Type>(r#""boolean ""#),
+            Err(Error::UnknownVariant("boolean ".into()))
+        );
+        assert_eq!(
+            crate::from_str::<Type>(r#""0123456789012345678901234567890123456789""#),
+            Err(Error::UnknownVariant(
+                "01234567890123456789012345678901".into()
+            ))
+        );
     }
 
     #[test]
@@ -952,10 +1797,15 @@ mod tests {
         );
 
         assert_eq!(
-            crate::from_str(r#"{ "temperature": 20, "invalid": this-is-ignored }"#),
+            crate::from_str(r#"{ "temperature": 20, "note": "say \"hi\"\u00e9", "n": -0.5E+3 }"#),
             Ok(Temperature { temperature: 20 })
         );
 
+        assert_eq!(
+            crate::from_str::<Temperature>(r#"{ "temperature": 20, "invalid": this-is-ignored }"#),
+            Err(crate::de::Error::ExpectedSomeIdent)
+        );
+
         assert_eq!(
             crate::from_str::<Temperature>(r#"{ "temperature": 20, "broken": }"#),
             Err(crate::de::Error::ExpectedSomeValue)
@@ -972,6 +1822,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ignoring_invalid_fields() {
+        use core::fmt::Write;
+
+        use crate::de::Error;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: u8,
+        }
+
+        fn skip(value: &str) -> crate::de::Result<Temperature> {
+            let mut json: heapless::String<heapless::consts::U64> = heapless::String::new();
+            write!(json, r#"{{"extra": {}, "temperature": 20}}"#, value).unwrap();
+            crate::from_str(&json)
+        }
+
+        assert_eq!(skip("nul"), Err(Error::ExpectedSomeIdent));
+        assert_eq!(skip("truth"), Err(Error::ExpectedSomeIdent));
+        assert_eq!(skip("falsey"), Err(Error::ExpectedObjectCommaOrEnd));
+        assert_eq!(skip("1 2"), Err(Error::ExpectedObjectCommaOrEnd));
+        assert_eq!(skip("1.e5"), Err(Error::InvalidNumber));
+        assert_eq!(skip("-"), Err(Error::InvalidNumber));
+        assert_eq!(skip("+1"), Err(Error::ExpectedSomeValue));
+        assert_eq!(skip("1e"), Err(Error::InvalidNumber));
+        assert_eq!(skip(r#""\x""#), Err(Error::InvalidEscape));
+        assert_eq!(skip(r#""\u12g4""#), Err(Error::InvalidEscape));
+        assert_eq!(skip(r#"["a", tru]"#), Err(Error::ExpectedSomeIdent));
+        assert_eq!(skip(r#"{"a": 1x}"#), Err(Error::ExpectedObjectCommaOrEnd));
+
+        assert_eq!(skip(r#"[1, {"a": [}]"#), Err(Error::ExpectedSomeValue));
+        assert_eq!(
+            skip(r#"[1, {"a": 2]]"#),
+            Err(Error::ExpectedObjectCommaOrEnd)
+        );
+        assert_eq!(skip(r#"{"a": [2}}"#), Err(Error::ExpectedListCommaOrEnd));
+        assert_eq!(skip(r#"[1, ]"#), Err(Error::TrailingComma));
+        assert_eq!(skip(r#"{"a": 1, }"#), Err(Error::TrailingComma));
+        assert_eq!(skip(r#"{"a" 1}"#), Err(Error::ExpectedColon));
+        assert_eq!(skip(r#"{1: 1}"#), Err(Error::KeyMustBeAString));
+
+        // an escaped quote doesn't end the string
+        assert_eq!(
+            skip(r#""\", \"temperature\": 99""#),
+            Ok(Temperature { temperature: 20 })
+        );
+    }
+
+    #[test]
+    fn ignoring_deeply_nested_fields() {
+        use heapless::{consts::U1024, String};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: u8,
+        }
+
+        fn nested(depth: usize) -> String<U1024> {
+            let mut json = String::new();
+            json.push_str(r#"{"temperature": 20, "extra": "#).unwrap();
+            for _ in 0..depth {
+                json.push_str(r#"[{"a":"#).unwrap();
+            }
+            json.push_str("null").unwrap();
+            for _ in 0..depth {
+                json.push_str("}]").unwrap();
+            }
+            json.push('}').unwrap();
+            json
+        }
+
+        assert_eq!(
+            crate::from_str(&nested(64)),
+            Ok(Temperature { temperature: 20 })
+        );
+        assert_eq!(
+            crate::from_str::<Temperature>(&nested(65)),
+            Err(crate::de::Error::DepthLimitExceeded)
+        );
+    }
+
     #[test]
     #[cfg(feature = "custom-error-messages")]
     fn preserve_short_error_message() {
@@ -1077,4 +2008,485 @@ mod tests {
             })
         )
     }
+
+    #[test]
+    fn limits() {
+        use crate::de::Error;
+        use crate::Config;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Reading<'a> {
+            id: &'a str,
+            values: [u8; 2],
+        }
+
+        let input = r#"{"id":"abcd","values":[1,2]}"#;
+        let reading = Reading {
+            id: "abcd",
+            values: [1, 2],
+        };
+        let limits = Config::new()
+            .max_depth(2)
+            .max_string_len(6)
+            .max_elements(2)
+            .max_members(2);
+        assert_eq!(parse_with(input, limits), Ok(reading));
+
+        let config = Config::new().max_depth(1);
+        assert_eq!(
+            parse_with::<Reading<'_>>(input, config),
+            Err(Error::DepthLimitExceeded)
+        );
+        assert_eq!(parse_with::<[u8; 0]>("[]", config), Ok([]));
+        assert_eq!(
+            parse_with::<Reading<'_>>(r#"{"id":"a","values":[1,2],"x":[[]]}"#, config.max_depth(2)),
+            Err(Error::DepthLimitExceeded)
+        );
+        assert_eq!(
+            parse_with::<Reading<'_>>(r#"{"id":"a","values":[1,2],"x":[{}]}"#, config.max_depth(3)),
+            Ok(Reading {
+                id: "a",
+                values: [1, 2]
+            })
+        );
+
+        let config = Config::new().max_string_len(3);
+        assert_eq!(
+            parse_with::<Reading<'_>>(input, config),
+            Err(Error::StringTooLong)
+        );
+        assert_eq!(
+            parse_with::<Reading<'_>>(r#"{"id":"a","values":[1,2],"note":"abcd"}"#, config),
+            Err(Error::StringTooLong)
+        );
+
+        let config = Config::new().max_elements(1);
+        assert_eq!(
+            parse_with::<Reading<'_>>(input, config),
+            Err(Error::TooManyElements)
+        );
+
+        let config = Config::new().max_members(1);
+        assert_eq!(
+            parse_with::<Reading<'_>>(input, config),
+            Err(Error::TooManyMembers)
+        );
+    }
+
+    #[test]
+    fn strict() {
+        use crate::de::Error;
+        use crate::Config;
+
+        let strict = Config::new().strict(true);
+
+        assert_eq!(parse_with("[-1.5e3, 0.25]", strict), Ok([-1.5e3, 0.25]));
+        assert_eq!(parse_with("[0, -0, 10]", strict), Ok([0, 0, 10]));
+        assert_eq!(parse_with(r#""a\"\u00e9""#, strict), Ok(r#"a\"\u00e9"#));
+
+        // lenient by default
+        assert_eq!(crate::from_str("1."), Ok(1.));
+        assert_eq!(crate::from_str(".5"), Ok(0.5));
+        assert_eq!(crate::from_str("\"a\tb\""), Ok("a\tb"));
+        assert_eq!(crate::from_str(r#""\x""#), Ok(r#"\x"#));
+
+        assert_eq!(
+            parse_with::<[f32; 1]>("[1.]", strict),
+            Err(Error::InvalidNumber)
+        );
+        assert_eq!(parse_with::<f32>(".5", strict), Err(Error::InvalidNumber));
+        assert_eq!(parse_with::<f32>("+1", strict), Err(Error::InvalidNumber));
+        assert_eq!(parse_with::<f32>("01", strict), Err(Error::InvalidNumber));
+        assert_eq!(
+            parse_with::<f32>("1e", strict),
+            Err(Error::EofWhileParsingNumber)
+        );
+        assert_eq!(parse_with::<u8>("01", strict), Err(Error::InvalidNumber));
+        assert_eq!(parse_with::<i8>("-01", strict), Err(Error::InvalidNumber));
+        assert_eq!(
+            parse_with::<&str>("\"a\tb\"", strict),
+            Err(Error::ControlCharacterInString)
+        );
+        assert_eq!(
+            parse_with::<&str>(r#""\x""#, strict),
+            Err(Error::InvalidEscape)
+        );
+    }
+
+    #[test]
+    fn lenient() {
+        use heapless::consts::U8;
+        use serde::Deserialize;
+
+        use crate::de::{Deserializer, Error};
+        use crate::{Config, Value};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Led<'a> {
+            name: &'a str,
+            color: [u8; 3],
+            brightness: i16,
+        }
+
+        let lenient = Config::new().lenient(true);
+
+        let input = r#"
+            // status LED
+            {
+                name: 'it\'s "on"', /* single quotes */
+                color: [0xFF, 0x80, 0,],
+                $unused: {a: [1, 2,], 'b': 0x1f, /* nothing */ },
+                brightness: -0x10,
+            }
+            // the end"#;
+        let led = Led {
+            name: r#"it\'s "on""#,
+            color: [255, 128, 0],
+            brightness: -16,
+        };
+        assert_eq!(parse_with(input, lenient), Ok(led));
+        assert_eq!(parse_with("[1, 2, 3,]", lenient), Ok([1, 2, 3]));
+        assert_eq!(
+            parse_with::<[u8; 1]>("[0X7f] /* unterminated", lenient),
+            Err(Error::TrailingCharacters)
+        );
+        assert_eq!(
+            parse_with::<[u8; 2]>("[0X7f /* unterminated ]", lenient),
+            Err(Error::ExpectedListCommaOrEnd)
+        );
+        assert_eq!(parse_with("[0X7f] // to the end", lenient), Ok([127u8]));
+
+        let value = parse_with::<Value<'_, U8>>("{a: [-0x10, 'b',],}", lenient).unwrap();
+        let a = value.root().get("a").unwrap();
+        assert_eq!(a.get_index(0).unwrap().as_i64(), Some(-16));
+        assert_eq!(a.get_index(1).unwrap().as_str(), Some("b"));
+
+        assert_eq!(
+            parse_with::<[u8; 1]>("[,]", lenient),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(
+            parse_with::<[u8; 2]>("[1,,]", lenient),
+            Err(Error::InvalidType)
+        );
+        assert_eq!(
+            parse_with::<[u8; 0]>("[ /* */ ,]", lenient),
+            Err(Error::ExpectedSomeValue)
+        );
+        assert_eq!(parse_with::<[u8; 0]>("[]", lenient), Ok([]));
+        assert_eq!(
+            parse_with::<Value<'_, U8>>("[,]", lenient).map(drop),
+            Err(Error::ExpectedSomeValue)
+        );
+        assert_eq!(
+            parse_with::<Value<'_, U8>>("{,}", lenient).map(drop),
+            Err(Error::KeyMustBeAString)
+        );
+        assert_eq!(parse_with::<u8>("0x", lenient), Err(Error::InvalidNumber));
+        assert_eq!(
+            parse_with::<u8>("0x100", lenient),
+            Err(Error::InvalidNumber)
+        );
+        assert_eq!(
+            parse_with::<i8>("-0x81", lenient),
+            Err(Error::InvalidNumber)
+        );
+        assert_eq!(
+            parse_with::<u8>("1 / 2", lenient),
+            Err(Error::TrailingCharacters)
+        );
+        assert_eq!(
+            parse_with::<Led<'_>>("{1: 0}", lenient),
+            Err(Error::KeyMustBeAString)
+        );
+
+        // strict JSON by default
+        assert_eq!(
+            crate::from_str::<[u8; 1]>("[1] // one"),
+            Err(Error::TrailingCharacters)
+        );
+        assert_eq!(
+            crate::from_str::<[u8; 1]>("[1,]"),
+            Err(Error::TrailingComma)
+        );
+        assert_eq!(crate::from_str::<&str>("'a'"), Err(Error::InvalidType));
+        assert_eq!(crate::from_str::<u8>("0x1"), Err(Error::TrailingCharacters));
+        assert_eq!(
+            crate::from_str::<Led<'_>>("{name: 'a'}"),
+            Err(Error::KeyMustBeAString)
+        );
+
+        // unquoted keys are limited like quoted ones
+        let config = Config::new().lenient(true).max_string_len(2);
+        for input in [&b"{abcdef: 1}"[..], b"{'abcdef': 1}", b"{\"abcdef\": 1}"].iter() {
+            let mut de = Deserializer::with_config(input, config);
+            assert_eq!(
+                Value::<'_, U8>::deserialize(&mut de).map(drop),
+                Err(Error::StringTooLong)
+            );
+            assert_eq!(
+                Deserializer::with_config(input, config).validate(),
+                Err(Error::StringTooLong)
+            );
+        }
+
+        // whichever of strict and lenient mode was enabled last applies
+        let config = Config::new().strict(true).lenient(true);
+        assert!(config.lenient && !config.strict);
+        let config = Config::new().lenient(true).strict(true);
+        assert!(config.strict && !config.lenient);
+        let mut de = Deserializer::with_config(b"[1,]", config);
+        assert_eq!(<[u8; 1]>::deserialize(&mut de), Err(Error::TrailingComma));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        use core::fmt::Write;
+
+        use heapless::consts::U16;
+
+        use crate::de::Error;
+        use crate::{Config, Value};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Point {
+            x: i8,
+            y: i8,
+        }
+
+        let config = Config::new().deny_duplicate_keys(true);
+        assert_eq!(
+            parse_with(r#"{"x": 1, "z": {"x": 0, "z": 0}, "y": 2}"#, config),
+            Ok(Point { x: 1, y: 2 })
+        );
+        assert_eq!(
+            parse_with::<Point>(r#"{"x": 1, "y": 2, "x": 3}"#, config),
+            Err(Error::DuplicateKey)
+        );
+        assert_eq!(
+            parse_with::<Point>(r#"{"x": 1, "z": [{"z": 0}], "y": 2, "z": 3}"#, config),
+            Err(Error::DuplicateKey)
+        );
+        assert_eq!(
+            parse_with::<Point>(r#"{"x": 1, "y": 2, "\u0078": 3}"#, config),
+            Ok(Point { x: 1, y: 2 })
+        );
+        assert_eq!(
+            parse_with::<Point>("{x: 1, y: 2, 'x': 3}", config.lenient(true)),
+            Err(Error::DuplicateKey)
+        );
+
+        assert!(parse_with::<Value<'_, U16>>(r#"{"a": {"a": 1}, "b": {"a": 1}}"#, config).is_ok());
+        assert_eq!(
+            parse_with::<Value<'_, U16>>(r#"{"a": 1, "b": [1, 2], "a": 2}"#, config).err(),
+            Some(Error::DuplicateKey)
+        );
+
+        // serde reports repeated struct fields, but not repeated map keys
+        assert_eq!(
+            parse_with::<Point>(r#"{"x": 1, "y": 2, "x": 3}"#, Config::new()),
+            Err(Error::DuplicateField("x"))
+        );
+        assert!(parse_with::<Value<'_, U16>>(r#"{"a": 1, "a": 2}"#, Config::new()).is_ok());
+
+        // skipped values are checked too
+        assert_eq!(
+            parse_with::<Point>(r#"{"x": 1, "z": {"a": 0, "a": 0}, "y": 2}"#, config),
+            Err(Error::DuplicateKey)
+        );
+
+        // up to 32 keys per object are remembered
+        let mut json: heapless::String<heapless::consts::U512> = heapless::String::new();
+        json.push_str(r#"{"x": 1, "y": 2"#).unwrap();
+        for i in 2..32 {
+            write!(json, r#", "k{}": 0"#, i).unwrap();
+        }
+        json.push_str("}").unwrap();
+        assert_eq!(parse_with(&json, config), Ok(Point { x: 1, y: 2 }));
+        json.pop();
+        json.push_str(r#", "k32": 3}"#).unwrap();
+        assert_eq!(
+            parse_with::<Point>(&json, config),
+            Err(Error::KeyLimitExceeded)
+        );
+        assert_eq!(parse_with(&json, Config::new()), Ok(Point { x: 1, y: 2 }));
+    }
+
+    #[test]
+    fn validate() {
+        use crate::de::{validate, Deserializer, Error};
+        use crate::Config;
+
+        assert_eq!(validate(b" [1, -2.5e3, \"a\\n\", {\"b\": null}] "), Ok(()));
+        assert_eq!(validate(b"[1, 2"), Err(Error::EofWhileParsingList));
+        assert_eq!(validate(b"[1] 2"), Err(Error::TrailingCharacters));
+        assert_eq!(validate(b""), Err(Error::EofWhileParsingValue));
+
+        let mut de = Deserializer::new(b"{\"a\": [1, 2,]}");
+        assert_eq!(de.validate(), Err(Error::TrailingComma));
+        assert_eq!(de.offset(), 12);
+
+        let mut de = Deserializer::new(b"{\"a\": \"\\x\"}");
+        assert_eq!(de.validate(), Err(Error::InvalidEscape));
+        assert_eq!(de.offset(), 8);
+
+        let config = Config::new().max_depth(2);
+        assert_eq!(
+            Deserializer::with_config(b"[[1]]", config).validate(),
+            Ok(())
+        );
+        let mut de = Deserializer::with_config(b"[[[1]]]", config);
+        assert_eq!(de.validate(), Err(Error::DepthLimitExceeded));
+        assert_eq!(de.offset(), 2);
+
+        let config = Config::new().max_elements(2);
+        assert_eq!(
+            Deserializer::with_config(b"[[1, 2], [], {\"a\": [3]}]", config).validate(),
+            Err(Error::TooManyElements)
+        );
+        let mut de = Deserializer::with_config(b"[[1, 2], [3, 4, 5]]", config);
+        assert_eq!(de.validate(), Err(Error::TooManyElements));
+        assert_eq!(de.offset(), 16);
+        let mut de = Deserializer::with_config(b"[[1, 2], {\"a\": [3, 4]}]", config);
+        assert_eq!(de.validate(), Ok(()));
+
+        let config = Config::new().max_members(1);
+        let mut de = Deserializer::with_config(b"{\"a\": {\"b\": 1}, \"c\": 2}", config);
+        assert_eq!(de.validate(), Err(Error::TooManyMembers));
+        assert_eq!(de.offset(), 19);
+        let mut de = Deserializer::with_config(b"{\"a\": [{\"b\": 1}, {\"b\": 2}]}", config);
+        assert_eq!(de.validate(), Ok(()));
+
+        let config = Config::new().deny_duplicate_keys(true);
+        let mut de = Deserializer::with_config(b"{\"a\": {\"a\": 1, \"b\": 2}, \"a\": 3}", config);
+        assert_eq!(de.validate(), Err(Error::DuplicateKey));
+        assert_eq!(de.offset(), 27);
+        let mut de = Deserializer::with_config(b"[{\"a\": 1}, {\"a\": {\"a\": 2}}]", config);
+        assert_eq!(de.validate(), Ok(()));
+        let mut deep = [b'['; 34];
+        deep[33] = b'1';
+        let mut de = Deserializer::with_config(&deep, config);
+        assert_eq!(de.validate(), Err(Error::DepthLimitExceeded));
+        assert_eq!(de.offset(), 33);
+    }
+
+    #[test]
+    fn unsupported_types() {
+        use core::marker::PhantomData;
+
+        use serde::de::{self, Deserializer as _, IgnoredAny};
+
+        use crate::de::{Deserializer, Error};
+
+        macro_rules! assert_unsupported {
+            ($input:expr, $($method:ident($($arg:expr),*)),+) => {$(
+                assert_eq!(
+                    Deserializer::new($input).$method($($arg,)* IgnoredAny),
+                    Err(Error::UnsupportedType)
+                );
+            )+};
+        }
+
+        assert_unsupported!(
+            b"0",
+            deserialize_char(),
+            deserialize_string(),
+            deserialize_bytes(),
+            deserialize_byte_buf(),
+            deserialize_unit(),
+            deserialize_unit_struct("Unit"),
+            deserialize_newtype_struct("Newtype")
+        );
+
+        // Deserializes the first key of an object as a `K`
+        struct FirstKey<K>(PhantomData<K>);
+
+        impl<'de, K> de::Deserialize<'de> for FirstKey<K>
+        where
+            K: de::Deserialize<'de>,
+        {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                struct Visitor<K>(PhantomData<K>);
+
+                impl<'de, K> de::Visitor<'de> for Visitor<K>
+                where
+                    K: de::Deserialize<'de>,
+                {
+                    type Value = FirstKey<K>;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut core::fmt::Formatter<'_>,
+                    ) -> core::fmt::Result {
+                        formatter.write_str("an object")
+                    }
+
+                    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: de::MapAccess<'de>,
+                    {
+                        map.next_key::<K>()?;
+                        Ok(FirstKey(PhantomData))
+                    }
+                }
+
+                deserializer.deserialize_map(Visitor(PhantomData))
+            }
+        }
+
+        #[allow(dead_code)]
+        #[derive(Deserialize)]
+        struct Unit;
+
+        #[allow(dead_code)]
+        #[derive(Deserialize)]
+        struct Newtype(u8);
+
+        #[allow(dead_code)]
+        #[derive(Deserialize)]
+        struct Tuple(u8, u8);
+
+        #[allow(dead_code)]
+        #[derive(Deserialize)]
+        struct Struct {
+            a: u8,
+        }
+
+        macro_rules! assert_unsupported_key {
+            ($($ty:ty),+) => {$(
+                assert_eq!(
+                    crate::from_str::<FirstKey<$ty>>(r#"{ "0": 0 }"#).err(),
+                    Some(Error::UnsupportedType)
+                );
+            )+};
+        }
+
+        assert_unsupported_key!(
+            bool,
+            i8,
+            i16,
+            i32,
+            i64,
+            u8,
+            u16,
+            u32,
+            u64,
+            f32,
+            f64,
+            char,
+            &[u8],
+            (),
+            Unit,
+            Newtype,
+            Option<u8>,
+            [u8; 1],
+            (u8, u8),
+            Tuple,
+            Struct,
+            Type
+        );
+    }
 }
@@ -0,0 +1,115 @@
+use core::fmt;
+use core::str;
+
+use heapless::{consts::U8, Vec};
+
+/// One step of the path recorded while deserializing; keys are stored as byte ranges of the input
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RawSegment {
+    Key(usize, usize),
+    Index(usize),
+}
+
+/// Fixed-size stack of the object keys and array indices leading to the value being deserialized
+///
+/// Only the outermost 8 levels are recorded; deeper levels are only counted.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PathStack {
+    segments: Vec<RawSegment, U8>,
+    truncated: usize,
+}
+
+impl PathStack {
+    pub(crate) fn new() -> Self {
+        PathStack {
+            segments: Vec::new(),
+            truncated: 0,
+        }
+    }
+
+    pub(crate) fn push_key(&mut self, start: usize, end: usize) {
+        self.push(RawSegment::Key(start, end))
+    }
+
+    pub(crate) fn push_index(&mut self, index: usize) {
+        self.push(RawSegment::Index(index))
+    }
+
+    fn push(&mut self, segment: RawSegment) {
+        if self.truncated != 0 || self.segments.push(segment).is_err() {
+            self.truncated += 1;
+        }
+    }
+
+    pub(crate) fn pop(&mut self) {
+        if self.truncated != 0 {
+            self.truncated -= 1;
+        } else {
+            self.segments.pop();
+        }
+    }
+}
+
+/// An object key or array index that is part of a `Path`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Segment<'a> {
+    /// An object key
+    Key(&'a str),
+    /// An array index
+    Index(usize),
+}
+
+/// The path to the value that failed to deserialize, e.g. `config.wifi.channels[3]`
+///
+/// Returned by `PositionedError::path`.
+#[derive(Clone, Copy, Debug)]
+pub struct Path<'a> {
+    input: &'a [u8],
+    stack: &'a PathStack,
+}
+
+impl<'a> Path<'a> {
+    pub(crate) fn new(input: &'a [u8], stack: &'a PathStack) -> Self {
+        Path { input, stack }
+    }
+
+    /// Returns an iterator over the recorded segments, outermost first
+    pub fn segments(&self) -> impl Iterator<Item = Segment<'a>> + 'a {
+        let input = self.input;
+        self.stack
+            .segments
+            .iter()
+            .map(move |segment| match *segment {
+                RawSegment::Key(start, end) => Segment::Key(
+                    input
+                        .get(start..end)
+                        .and_then(|key| str::from_utf8(key).ok())
+                        .unwrap_or(""),
+                ),
+                RawSegment::Index(index) => Segment::Index(index),
+            })
+    }
+
+    /// Returns `true` if the path was deeper than what could be recorded
+    pub fn is_truncated(&self) -> bool {
+        self.stack.truncated != 0
+    }
+}
+
+impl<'a> fmt::Display for Path<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments().enumerate() {
+            match segment {
+                Segment::Key(key) if i == 0 => f.write_str(key)?,
+                Segment::Key(key) => write!(f, ".{}", key)?,
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+
+        if self.is_truncated() {
+            f.write_str("...")?;
+        }
+
+        Ok(())
+    }
+}
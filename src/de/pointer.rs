@@ -0,0 +1,263 @@
+use core::str::FromStr;
+
+use serde::de;
+
+use crate::de::{Deserializer, Error, Result};
+use crate::value::RawValue;
+
+/// Looks up the value at `pointer`, a JSON Pointer as defined in RFC 6901, without deserializing
+/// the rest of the document
+///
+/// Objects and arrays on the way to the value are walked member by member and the siblings of the
+/// value are skipped over, the same way unknown struct fields are. The input after the value is
+/// not looked at. Returns `Ok(None)` if the document has no value at `pointer`.
+///
+/// Object keys are compared with the reference tokens of `pointer` as they are written in the
+/// input, so keys that contain escape sequences can't be looked up.
+///
+/// Only standard JSON is accepted: the comments, trailing commas and other extensions of
+/// `Config::lenient` are reported as errors.
+///
+/// ```
+/// let shadow = br#"{"state": {"reported": {"led": false}, "desired": {"led": true}}}"#;
+///
+/// let led = serde_json_core::de::pointer(shadow, "/state/desired/led").unwrap();
+/// assert_eq!(led.map(|v| v.get()), Some("true"));
+///
+/// assert_eq!(serde_json_core::de::pointer(shadow, "/state/delta"), Ok(None));
+/// ```
+pub fn pointer<'a>(v: &'a [u8], pointer: &str) -> Result<Option<RawValue<'a>>> {
+    check(pointer)?;
+
+    let mut de = Deserializer::new(v);
+    for token in pointer.split('/').skip(1) {
+        if !step(&mut de, token)? {
+            return Ok(None);
+        }
+    }
+
+    de.parse_raw()
+        .map(|json| Some(RawValue::new_unchecked(json)))
+}
+
+/// Deserializes the value at `pointer` as a `T`, leaving the rest of the document alone
+///
+/// Returns `Ok(None)` if the document has no value at `pointer`; see `pointer` for how the
+/// document is walked.
+///
+/// ```
+/// let shadow = br#"{"state": {"desired": {"color": [255, 128, 0]}}}"#;
+///
+/// let green = serde_json_core::de::from_slice_at::<u8>(shadow, "/state/desired/color/1");
+/// assert_eq!(green, Ok(Some(128)));
+/// ```
+pub fn from_slice_at<'a, T>(v: &'a [u8], pointer: &str) -> Result<Option<T>>
+where
+    T: de::Deserialize<'a>,
+{
+    match self::pointer(v, pointer)? {
+        Some(value) => value.parse().map(Some),
+        None => Ok(None),
+    }
+}
+
+fn check(pointer: &str) -> Result<()> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return Err(Error::InvalidPointer);
+    }
+
+    let mut bytes = pointer.bytes();
+    while let Some(c) = bytes.next() {
+        if c == b'~' {
+            match bytes.next() {
+                Some(b'0') | Some(b'1') => {}
+                _ => return Err(Error::InvalidPointer),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves `de` to the value that `token` refers to in the next value, and returns whether there is
+/// one
+fn step(de: &mut Deserializer<'_>, token: &str) -> Result<bool> {
+    match de.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+        b'{' => {
+            de.eat_char();
+            let mut peek = de.parse_whitespace().ok_or(Error::EofWhileParsingObject)?;
+            if peek == b'}' {
+                return Ok(false);
+            }
+
+            loop {
+                match peek {
+                    b'"' => de.eat_char(),
+                    b'}' => return Err(Error::TrailingComma),
+                    _ => return Err(Error::KeyMustBeAString),
+                }
+                let key = de.parse_str(b'"')?;
+                de.parse_object_colon()?;
+                if key_matches(key, token) {
+                    return Ok(true);
+                }
+
+                de.skip_value()?;
+                match de.parse_whitespace().ok_or(Error::EofWhileParsingObject)? {
+                    b',' => {
+                        de.eat_char();
+                        peek = de.parse_whitespace().ok_or(Error::EofWhileParsingObject)?;
+                    }
+                    b'}' => return Ok(false),
+                    _ => return Err(Error::ExpectedObjectCommaOrEnd),
+                }
+            }
+        }
+        b'[' => {
+            de.eat_char();
+            let index = match parse_index(token) {
+                Some(index) => index,
+                None => return Ok(false),
+            };
+            if de.parse_whitespace().ok_or(Error::EofWhileParsingList)? == b']' {
+                return Ok(false);
+            }
+
+            for _ in 0..index {
+                de.skip_value()?;
+                match de.parse_whitespace().ok_or(Error::EofWhileParsingList)? {
+                    b',' => de.eat_char(),
+                    b']' => return Ok(false),
+                    _ => return Err(Error::ExpectedListCommaOrEnd),
+                }
+                if de.parse_whitespace().ok_or(Error::EofWhileParsingList)? == b']' {
+                    return Err(Error::TrailingComma);
+                }
+            }
+
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Compares an object key, as written in the input, with an escaped reference token
+fn key_matches(key: &str, token: &str) -> bool {
+    let mut key = key.bytes();
+    let mut token = token.bytes();
+    loop {
+        let expected = match token.next() {
+            Some(b'~') => match token.next() {
+                Some(b'0') => b'~',
+                _ => b'/',
+            },
+            Some(c) => c,
+            None => return key.next().is_none(),
+        };
+
+        if key.next() != Some(expected) {
+            return false;
+        }
+    }
+}
+
+/// Parses an array index; leading zeros are not allowed
+fn parse_index(token: &str) -> Option<usize> {
+    if token.len() > 1 && token.starts_with('0') || !token.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    usize::from_str(token).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_derive::Deserialize;
+
+    use super::{from_slice_at, pointer};
+    use crate::de::Error;
+
+    const SHADOW: &[u8] = br#"{
+        "state": {
+            "reported": {"led": false, "color": [0, 0, 0]},
+            "desired": {"led": true, "color": [255, 128, 0], "a/b": 1, "m~n": 2}
+        },
+        "version": 7
+    }"#;
+
+    fn get<'a>(v: &'a [u8], p: &str) -> Option<&'a str> {
+        pointer(v, p).unwrap().map(|value| value.get())
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(get(SHADOW, "/version"), Some("7"));
+        assert_eq!(get(SHADOW, "/state/desired/led"), Some("true"));
+        assert_eq!(get(SHADOW, "/state/reported/color"), Some("[0, 0, 0]"));
+        assert_eq!(get(SHADOW, "/state/desired/color/2"), Some("0"));
+        assert_eq!(get(SHADOW, "/state/desired/a~1b"), Some("1"));
+        assert_eq!(get(SHADOW, "/state/desired/m~0n"), Some("2"));
+        assert_eq!(get(br#" "hi" "#, ""), Some(r#""hi""#));
+    }
+
+    #[test]
+    fn missing() {
+        assert_eq!(get(SHADOW, "/state/delta"), None);
+        assert_eq!(get(SHADOW, "/state/desired/color/3"), None);
+        assert_eq!(get(SHADOW, "/state/desired/color/01"), None);
+        assert_eq!(get(SHADOW, "/state/desired/color/-"), None);
+        assert_eq!(get(SHADOW, "/version/0"), None);
+        assert_eq!(get(b"[]", "/0"), None);
+        assert_eq!(get(b"{}", "/a"), None);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(pointer(SHADOW, "state"), Err(Error::InvalidPointer));
+        assert_eq!(pointer(SHADOW, "/state/~2"), Err(Error::InvalidPointer));
+        assert_eq!(
+            pointer(br#"{"a": [1] "b": 2}"#, "/b"),
+            Err(Error::ExpectedObjectCommaOrEnd)
+        );
+        assert_eq!(pointer(b"[1, 2,]", "/2"), Err(Error::TrailingComma));
+        assert_eq!(
+            pointer(br#"{"a": "#, "/a"),
+            Err(Error::EofWhileParsingValue)
+        );
+    }
+
+    #[test]
+    fn standard_json_only() {
+        assert_eq!(pointer(b"{a: 1}", "/a"), Err(Error::KeyMustBeAString));
+        assert_eq!(
+            pointer(br#"{/* x */ "a": 1}"#, "/a"),
+            Err(Error::KeyMustBeAString)
+        );
+        assert_eq!(
+            from_slice_at::<[u8; 1]>(br#"{"a": [1,]}"#, "/a"),
+            Err(Error::TrailingComma)
+        );
+    }
+
+    #[test]
+    fn subtree() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Desired {
+            led: bool,
+            color: [u8; 3],
+        }
+
+        assert_eq!(
+            from_slice_at(SHADOW, "/state/desired"),
+            Ok(Some(Desired {
+                led: true,
+                color: [255, 128, 0],
+            }))
+        );
+        assert_eq!(from_slice_at::<u8>(SHADOW, "/state/delta"), Ok(None));
+        assert_eq!(
+            from_slice_at::<u8>(SHADOW, "/state/desired/led"),
+            Err(Error::InvalidType)
+        );
+    }
+}
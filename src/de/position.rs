@@ -0,0 +1,208 @@
+use core::fmt;
+
+use serde::de;
+
+#[cfg(feature = "error-path")]
+use crate::de::path::{Path, PathStack};
+use crate::de::{Deserializer, Error};
+
+/// A deserialization error along with the byte offset of the input at which it occurred
+#[derive(Debug, PartialEq)]
+pub struct PositionedError {
+    error: Error,
+    offset: usize,
+    #[cfg(feature = "error-path")]
+    path: PathStack,
+}
+
+impl PositionedError {
+    /// Returns the underlying error
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Consumes this value and returns the underlying error
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+
+    /// Returns the byte offset of the input at which the error occurred
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Computes the one-based line and column at which the error occurred
+    ///
+    /// `input` must be the slice that was being deserialized. Columns are counted in bytes.
+    pub fn line_column(&self, input: &[u8]) -> (usize, usize) {
+        let before = &input[..self.offset.min(input.len())];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+
+        (line, before.len() - line_start + 1)
+    }
+
+    /// Returns the object keys and array indices leading to the value that failed to deserialize
+    ///
+    /// `input` must be the slice that was being deserialized.
+    ///
+    /// ```
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Config {
+    ///     channels: [u8; 2],
+    /// }
+    ///
+    /// let input = br#"{ "channels":[1, -1] }"#;
+    /// let err = serde_json_core::de::from_slice_with_position::<Config>(input).unwrap_err();
+    /// assert_eq!(
+    ///     format!("{}: {}", err.path(input), err.error()),
+    ///     "channels[1]: Invalid number."
+    /// );
+    /// ```
+    #[cfg(feature = "error-path")]
+    pub fn path<'a>(&'a self, input: &'a [u8]) -> Path<'a> {
+        Path::new(input, &self.path)
+    }
+}
+
+impl fmt::Display for PositionedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at byte {})", self.error, self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for PositionedError {
+    fn description(&self) -> &str {
+        ""
+    }
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text, reporting where in the input
+/// deserialization failed
+// NOTE(allow) the error is only this large when the opt-in `error-path` feature is enabled
+#[allow(clippy::result_large_err)]
+pub fn from_slice_with_position<'a, T>(v: &'a [u8]) -> Result<T, PositionedError>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v);
+    de::Deserialize::deserialize(&mut de)
+        .and_then(|value| de.end().map(|_| value))
+        .map_err(|error| PositionedError {
+            error,
+            offset: de.offset(),
+            #[cfg(feature = "error-path")]
+            path: de.path.clone(),
+        })
+}
+
+/// Deserializes an instance of type `T` from a string of JSON text, reporting where in the input
+/// deserialization failed
+#[allow(clippy::result_large_err)]
+pub fn from_str_with_position<'a, T>(s: &'a str) -> Result<T, PositionedError>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_with_position(s.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_derive::Deserialize;
+
+    use crate::de::{from_str_with_position, Error};
+
+    #[test]
+    fn offset() {
+        let err = from_str_with_position::<[u8; 2]>("[1, x]").unwrap_err();
+        assert_eq!(*err.error(), Error::InvalidType);
+        assert_eq!(err.offset(), 4);
+
+        let err = from_str_with_position::<bool>("true false").unwrap_err();
+        assert_eq!(err.into_error(), Error::TrailingCharacters);
+    }
+
+    #[test]
+    fn line_column() {
+        #[derive(Debug, Deserialize)]
+        struct Led {
+            #[allow(dead_code)]
+            led: bool,
+        }
+
+        let input = "{\n  \"led\": tru\n}";
+        let err = from_str_with_position::<Led>(input).unwrap_err();
+        assert_eq!(*err.error(), Error::ExpectedSomeIdent);
+        assert_eq!(err.line_column(input.as_bytes()), (2, 13));
+    }
+
+    #[test]
+    #[cfg(feature = "error-path")]
+    fn path() {
+        use core::fmt::Write;
+
+        use heapless::{consts::U32, String, Vec};
+
+        use crate::de::Segment;
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Config<'a> {
+            #[serde(borrow)]
+            wifi: Wifi<'a>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Wifi<'a> {
+            ssid: &'a str,
+            channels: [u8; 4],
+        }
+
+        let input = br#"{ "wifi": { "ssid": "home", "channels":[1, 6, 11, 300] } }"#;
+        let err = crate::de::from_slice_with_position::<Config<'_>>(input).unwrap_err();
+        assert_eq!(*err.error(), Error::InvalidNumber);
+        assert_eq!(
+            err.path(input).segments().collect::<Vec<_, U32>>(),
+            [
+                Segment::Key("wifi"),
+                Segment::Key("channels"),
+                Segment::Index(3)
+            ]
+        );
+        let mut path = String::<U32>::new();
+        write!(path, "{}", err.path(input)).unwrap();
+        assert_eq!(path, "wifi.channels[3]");
+
+        // errors outside of any value have an empty path
+        let input = br#"{ "wifi": { "ssid": "home", "channels":[1, 6, 11, 13] } } x"#;
+        let err = crate::de::from_slice_with_position::<Config<'_>>(input).unwrap_err();
+        assert_eq!(*err.error(), Error::TrailingCharacters);
+        assert_eq!(err.path(input).segments().count(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "error-path")]
+    fn path_truncated() {
+        use core::fmt::Write;
+
+        use heapless::{consts::U32, String};
+
+        let input = b"[[[[[[[[[[true]]]]]]]]]]";
+        let err = crate::de::from_slice_with_position::<
+            [[[[[[[[[[u8; 1]; 1]; 1]; 1]; 1]; 1]; 1]; 1]; 1]; 1],
+        >(input)
+        .unwrap_err();
+        assert!(err.path(input).is_truncated());
+        let mut path = String::<U32>::new();
+        write!(path, "{}", err.path(input)).unwrap();
+        assert_eq!(path, "[0][0][0][0][0][0][0][0]...");
+    }
+}
@@ -0,0 +1,404 @@
+use heapless::{consts::U32, Vec};
+
+use crate::de::{Deserializer, Error, Result};
+use crate::Config;
+
+/// A JSON token, as returned by `Reader`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Token<'a> {
+    /// `{`
+    BeginObject,
+    /// `}`
+    EndObject,
+    /// `[`
+    BeginArray,
+    /// `]`
+    EndArray,
+    /// An object key. Escape sequences are left as is.
+    Key(&'a str),
+    /// A string value. Escape sequences are left as is.
+    String(&'a str),
+    /// A number, as written in the input, which follows the JSON number grammar
+    Number(&'a str),
+    /// `true` or `false`
+    Bool(bool),
+    /// `null`
+    Null,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Container {
+    Array,
+    Object,
+}
+
+/// A pull parser that walks a JSON document token by token, without a target type
+///
+/// Containers can be nested up to 32 levels deep, or less if the configuration sets a lower
+/// `max_depth`; deeper input is reported as `Error::DepthLimitExceeded`. Strings and keys are
+/// checked against the configured `max_string_len`. Iteration stops after the first error.
+///
+/// The strict and lenient modes of the configuration apply. In lenient mode, keys and strings are
+/// returned without their quotes whether they are single-quoted, double-quoted or unquoted, and
+/// hexadecimal integers are returned as written, like `0x1F`.
+///
+/// ```
+/// use serde_json_core::de::{Reader, Token};
+///
+/// let mut reader = Reader::new(br#"{"id": 7, "tags": ["a", "b"], "on": true}"#);
+/// assert_eq!(reader.next(), Some(Ok(Token::BeginObject)));
+/// assert_eq!(reader.next(), Some(Ok(Token::Key("id"))));
+/// assert_eq!(reader.next(), Some(Ok(Token::Number("7"))));
+/// assert_eq!(reader.next(), Some(Ok(Token::Key("tags"))));
+/// reader.skip_value().unwrap();
+/// assert_eq!(reader.next(), Some(Ok(Token::Key("on"))));
+/// assert_eq!(reader.next(), Some(Ok(Token::Bool(true))));
+/// assert_eq!(reader.next(), Some(Ok(Token::EndObject)));
+/// assert_eq!(reader.next(), None);
+/// ```
+pub struct Reader<'a> {
+    de: Deserializer<'a>,
+    stack: Vec<Container, U32>,
+    /// No element of the innermost container has been read yet
+    first: bool,
+    /// An object key has been read and its value hasn't
+    after_key: bool,
+    /// The top-level value has been started
+    started: bool,
+    /// An error was returned or the end of the input was reached
+    done: bool,
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a reader over `slice` with the default configuration
+    pub fn new(slice: &'a [u8]) -> Self {
+        Reader::with_config(slice, Config::new())
+    }
+
+    /// Creates a reader over `slice` with the given configuration
+    pub fn with_config(slice: &'a [u8], config: Config) -> Self {
+        Reader {
+            de: Deserializer::with_config(slice, config),
+            stack: Vec::new(),
+            first: true,
+            after_key: false,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Returns the number of bytes of the input consumed so far
+    pub fn offset(&self) -> usize {
+        self.de.offset()
+    }
+
+    /// Returns the number of containers the reader is currently in
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Skips the next value, including all of its contents if it's an object or array
+    ///
+    /// If the next token is an object key, the key and its value are skipped.
+    pub fn skip_value(&mut self) -> Result<()> {
+        let depth = self.depth();
+        loop {
+            match self.next() {
+                Some(Ok(Token::Key(_))) if self.depth() == depth => {}
+                Some(Ok(Token::EndObject)) | Some(Ok(Token::EndArray)) if self.depth() < depth => {
+                    self.done = true;
+                    return Err(Error::ExpectedSomeValue);
+                }
+                Some(Ok(_)) if self.depth() == depth => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e),
+                None => return Err(Error::EofWhileParsingValue),
+            }
+        }
+    }
+
+    fn parse_token(&mut self) -> Result<Option<Token<'a>>> {
+        let container = match self.stack.last() {
+            Some(container) => *container,
+            None if self.started => return self.de.end().map(|_| None),
+            None => {
+                self.started = true;
+                return self.parse_value().map(Some);
+            }
+        };
+
+        if self.after_key {
+            self.after_key = false;
+            self.de.parse_object_colon()?;
+            return self.parse_value().map(Some);
+        }
+
+        let (end, eof, expected_comma_or_end) = match container {
+            Container::Array => (
+                b']',
+                Error::EofWhileParsingList,
+                Error::ExpectedListCommaOrEnd,
+            ),
+            Container::Object => (
+                b'}',
+                Error::EofWhileParsingObject,
+                Error::ExpectedObjectCommaOrEnd,
+            ),
+        };
+
+        let mut peek = self.de.parse_whitespace().ok_or(eof)?;
+        if !self.first && peek != end {
+            if peek != b',' {
+                return Err(expected_comma_or_end);
+            }
+            self.de.eat_char();
+            peek = self
+                .de
+                .parse_whitespace()
+                .ok_or(Error::EofWhileParsingValue)?;
+            if peek == end && !self.de.config.lenient {
+                return Err(Error::TrailingComma);
+            }
+        }
+
+        if peek == end {
+            self.de.eat_char();
+            self.stack.pop();
+            self.first = false;
+            return Ok(Some(match container {
+                Container::Array => Token::EndArray,
+                Container::Object => Token::EndObject,
+            }));
+        }
+        self.first = false;
+
+        match container {
+            Container::Array => self.parse_value().map(Some),
+            Container::Object => {
+                let key = if self.de.is_quote(peek) {
+                    self.de.eat_char();
+                    self.de.parse_str(peek)?
+                } else if self.de.is_key_start(peek) {
+                    self.de.parse_ident_key()?
+                } else {
+                    return Err(Error::KeyMustBeAString);
+                };
+                self.after_key = true;
+                Ok(Some(Token::Key(key)))
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Token<'a>> {
+        match self
+            .de
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingValue)?
+        {
+            b'{' => self.begin(Container::Object),
+            b'[' => self.begin(Container::Array),
+            c if self.de.is_quote(c) => {
+                self.de.eat_char();
+                Ok(Token::String(self.de.parse_str(c)?))
+            }
+            b't' => {
+                self.de.eat_char();
+                self.de.parse_ident(b"rue")?;
+                Ok(Token::Bool(true))
+            }
+            b'f' => {
+                self.de.eat_char();
+                self.de.parse_ident(b"alse")?;
+                Ok(Token::Bool(false))
+            }
+            b'n' => {
+                self.de.eat_char();
+                self.de.parse_ident(b"ull")?;
+                Ok(Token::Null)
+            }
+            b'-' | b'0'..=b'9' => Ok(Token::Number(self.de.parse_checked_number()?)),
+            _ => Err(Error::ExpectedSomeValue),
+        }
+    }
+
+    fn begin(&mut self, container: Container) -> Result<Token<'a>> {
+        if self.stack.len() >= self.de.config.max_depth {
+            return Err(Error::DepthLimitExceeded);
+        }
+        self.stack
+            .push(container)
+            .map_err(|_| Error::DepthLimitExceeded)?;
+        self.de.eat_char();
+        self.first = true;
+
+        Ok(match container {
+            Container::Array => Token::BeginArray,
+            Container::Object => Token::BeginObject,
+        })
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<Token<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let token = self.parse_token();
+        match token {
+            Ok(Some(token)) => Some(Ok(token)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::de::{Error, Reader, Token};
+
+    #[test]
+    fn tokens() {
+        let input = br#" { "a" : [1, -2.5e3, "x", true, false, null, {}, []] , "b":{"c":"d"} } "#;
+        let tokens = [
+            Token::BeginObject,
+            Token::Key("a"),
+            Token::BeginArray,
+            Token::Number("1"),
+            Token::Number("-2.5e3"),
+            Token::String("x"),
+            Token::Bool(true),
+            Token::Bool(false),
+            Token::Null,
+            Token::BeginObject,
+            Token::EndObject,
+            Token::BeginArray,
+            Token::EndArray,
+            Token::EndArray,
+            Token::Key("b"),
+            Token::BeginObject,
+            Token::Key("c"),
+            Token::String("d"),
+            Token::EndObject,
+            Token::EndObject,
+        ];
+
+        let mut reader = Reader::new(input);
+        for token in tokens.iter() {
+            assert_eq!(reader.next(), Some(Ok(*token)));
+        }
+        assert_eq!(reader.next(), None);
+        assert_eq!(reader.offset(), input.len());
+    }
+
+    #[test]
+    fn scalar() {
+        let mut reader = Reader::new(b" 42 ");
+        assert_eq!(reader.next(), Some(Ok(Token::Number("42"))));
+        assert_eq!(reader.next(), None);
+
+        let mut reader = Reader::new(b"true false");
+        assert_eq!(reader.next(), Some(Ok(Token::Bool(true))));
+        assert_eq!(reader.next(), Some(Err(Error::TrailingCharacters)));
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn skip_value() {
+        let mut reader = Reader::new(br#"[{"a": [1, {"b": 2}]}, 3, "four"]"#);
+        assert_eq!(reader.next(), Some(Ok(Token::BeginArray)));
+        reader.skip_value().unwrap();
+        assert_eq!(reader.depth(), 1);
+        assert_eq!(reader.next(), Some(Ok(Token::Number("3"))));
+        reader.skip_value().unwrap();
+        assert_eq!(reader.next(), Some(Ok(Token::EndArray)));
+
+        let mut reader = Reader::new(br#"{"a": {"b": 2}, "c": 3}"#);
+        assert_eq!(reader.next(), Some(Ok(Token::BeginObject)));
+        reader.skip_value().unwrap();
+        assert_eq!(reader.next(), Some(Ok(Token::Key("c"))));
+
+        let mut reader = Reader::new(br#"[]"#);
+        assert_eq!(reader.next(), Some(Ok(Token::BeginArray)));
+        assert_eq!(reader.skip_value(), Err(Error::ExpectedSomeValue));
+    }
+
+    #[test]
+    fn errors() {
+        fn last_error(input: &[u8]) -> Option<Error> {
+            Reader::new(input).filter_map(|token| token.err()).next()
+        }
+
+        assert_eq!(last_error(b"[1 2]"), Some(Error::ExpectedListCommaOrEnd));
+        assert_eq!(last_error(b"[1,]"), Some(Error::TrailingComma));
+        assert_eq!(last_error(b"{1: 2}"), Some(Error::KeyMustBeAString));
+        assert_eq!(last_error(br#"{"a" 2}"#), Some(Error::ExpectedColon));
+        assert_eq!(
+            last_error(br#"{"a": 2"#),
+            Some(Error::EofWhileParsingObject)
+        );
+        assert_eq!(last_error(b"[nul]"), Some(Error::ExpectedSomeIdent));
+        assert_eq!(last_error(b"[1-2]"), Some(Error::InvalidNumber));
+        assert_eq!(last_error(b"[--5]"), Some(Error::InvalidNumber));
+        assert_eq!(last_error(b"[1.2.3]"), Some(Error::InvalidNumber));
+        assert_eq!(last_error(b"[1.]"), Some(Error::InvalidNumber));
+        assert_eq!(last_error(b"1e"), Some(Error::EofWhileParsingNumber));
+        assert_eq!(last_error(b"]"), Some(Error::ExpectedSomeValue));
+        assert_eq!(last_error(&[b'['; 33]), Some(Error::DepthLimitExceeded));
+        assert_eq!(last_error(&[b'['; 32]), Some(Error::EofWhileParsingList));
+    }
+
+    #[test]
+    fn lenient() {
+        use crate::Config;
+
+        let input = b"{a: ['b', 0x1F,], /* c */ 'd': {},} // e";
+        let tokens = [
+            Token::BeginObject,
+            Token::Key("a"),
+            Token::BeginArray,
+            Token::String("b"),
+            Token::Number("0x1F"),
+            Token::EndArray,
+            Token::Key("d"),
+            Token::BeginObject,
+            Token::EndObject,
+            Token::EndObject,
+        ];
+
+        let mut reader = Reader::with_config(input, Config::new().lenient(true));
+        for token in tokens.iter() {
+            assert_eq!(reader.next(), Some(Ok(*token)));
+        }
+        assert_eq!(reader.next(), None);
+
+        let last_error = |input: &[u8]| {
+            Reader::with_config(input, Config::new().lenient(true))
+                .filter_map(|token| token.err())
+                .next()
+        };
+        assert_eq!(last_error(b"[,]"), Some(Error::ExpectedSomeValue));
+        assert_eq!(last_error(b"{,}"), Some(Error::KeyMustBeAString));
+        assert_eq!(last_error(b"[1,,]"), Some(Error::ExpectedSomeValue));
+        assert_eq!(last_error(b"[0x]"), Some(Error::InvalidNumber));
+        assert_eq!(last_error(b"[1] /*"), Some(Error::TrailingCharacters));
+        assert_eq!(
+            Reader::with_config(b"{abc: 1}", Config::new().lenient(true).max_string_len(2)).nth(1),
+            Some(Err(Error::StringTooLong))
+        );
+
+        // standard JSON by default
+        assert_eq!(
+            Reader::new(b"[1] // one").last(),
+            Some(Err(Error::TrailingCharacters))
+        );
+    }
+}
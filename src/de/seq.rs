@@ -5,11 +5,17 @@ use crate::de::{Deserializer, Error, Result};
 pub(crate) struct SeqAccess<'a, 'b> {
     first: bool,
     de: &'a mut Deserializer<'b>,
+    /// Index of the next element
+    index: usize,
 }
 
 impl<'a, 'b> SeqAccess<'a, 'b> {
     pub fn new(de: &'a mut Deserializer<'b>) -> Self {
-        SeqAccess { de, first: true }
+        SeqAccess {
+            de,
+            first: true,
+            index: 0,
+        }
     }
 }
 
@@ -26,7 +32,7 @@ impl<'a, 'de> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
             .ok_or(Error::EofWhileParsingList)?
         {
             b']' => return Ok(None),
-            b',' => {
+            b',' if !self.first => {
                 self.de.eat_char();
                 self.de
                     .parse_whitespace()
@@ -43,9 +49,25 @@ impl<'a, 'de> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
         };
 
         if peek == b']' {
-            Err(Error::TrailingComma)
-        } else {
-            Ok(Some(seed.deserialize(&mut *self.de)?))
+            return if self.de.config.lenient {
+                Ok(None)
+            } else {
+                Err(Error::TrailingComma)
+            };
+        }
+        if self.index == self.de.config.max_elements {
+            return Err(Error::TooManyElements);
         }
+
+        #[cfg(feature = "error-path")]
+        self.de.path.push_index(self.index);
+
+        let value = seed.deserialize(&mut *self.de)?;
+
+        #[cfg(feature = "error-path")]
+        self.de.path.pop();
+        self.index += 1;
+
+        Ok(Some(value))
     }
 }
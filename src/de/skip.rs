@@ -0,0 +1,83 @@
+use heapless::{consts::U32, Vec};
+
+use crate::de::keys::KeySet;
+use crate::de::{Error, Result};
+use crate::Config;
+
+/// A container that is open while skipping a value
+struct Level {
+    /// Number of elements or members read so far
+    len: usize,
+    /// Number of keys of the enclosing objects in the key set
+    keys: usize,
+}
+
+/// What `Deserializer::skip_value` needs to apply the limits on the number of elements and members,
+/// and to deny duplicate keys
+///
+/// Up to 32 non-empty containers can be open at once; the keys of the open objects share one
+/// `KeySet`.
+pub(crate) struct SkipChecks {
+    levels: Vec<Level, U32>,
+    keys: KeySet,
+}
+
+impl SkipChecks {
+    pub(crate) fn new() -> Self {
+        SkipChecks {
+            levels: Vec::new(),
+            keys: KeySet::new(),
+        }
+    }
+
+    /// Enters an array or object that has at least one element or member
+    pub(crate) fn open(&mut self) -> Result<()> {
+        let level = Level {
+            len: 0,
+            keys: self.keys.len(),
+        };
+        self.levels
+            .push(level)
+            .map_err(|_| Error::DepthLimitExceeded)
+    }
+
+    /// Leaves the innermost container
+    pub(crate) fn close(&mut self) {
+        if let Some(level) = self.levels.pop() {
+            self.keys.truncate(level.keys);
+        }
+    }
+
+    /// Counts an element of the innermost container, an array
+    pub(crate) fn element(&mut self, config: &Config) -> Result<()> {
+        if let Some(level) = self.levels.last_mut() {
+            if level.len == config.max_elements {
+                return Err(Error::TooManyElements);
+            }
+            level.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Counts a member of the innermost container, an object, whose key spans `start..end` of
+    /// `slice`
+    pub(crate) fn member(
+        &mut self,
+        config: &Config,
+        slice: &[u8],
+        start: usize,
+        end: usize,
+    ) -> Result<()> {
+        if let Some(level) = self.levels.last_mut() {
+            if level.len == config.max_members {
+                return Err(Error::TooManyMembers);
+            }
+            level.len += 1;
+
+            if config.deny_duplicate_keys {
+                self.keys.insert(slice, level.keys, start, end)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use core::marker::PhantomData;
+use core::ops::Range;
+
+use serde::de;
+
+use crate::de::{Deserializer, Result};
+use crate::Config;
+
+/// RFC 7464 record separator
+const RS: u8 = 0x1e;
+
+/// An iterator over the JSON values of a slice
+///
+/// Values can be separated by whitespace (like in concatenated JSON and newline-delimited JSON)
+/// and by the record separator (`0x1E`) of JSON text sequences (RFC 7464). Each value is yielded
+/// along with the byte range it occupies in the slice.
+///
+/// When a value fails to deserialize its error is yielded and iteration resumes after the next
+/// newline or record separator that follows the start of the bad value.
+///
+/// ```
+/// use serde_json_core::de::StreamDeserializer;
+///
+/// let mut values = StreamDeserializer::<u8>::new(b"1\n256\n3");
+/// assert_eq!(values.next(), Some(Ok((1, 0..1))));
+/// assert!(values.next().unwrap().is_err());
+/// assert_eq!(values.next(), Some(Ok((3, 6..7))));
+/// assert_eq!(values.next(), None);
+/// ```
+pub struct StreamDeserializer<'a, T> {
+    de: Deserializer<'a>,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T> StreamDeserializer<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    /// Creates a stream of values over `slice` with the default configuration
+    pub fn new(slice: &'a [u8]) -> Self {
+        StreamDeserializer::with_config(slice, Config::new())
+    }
+
+    /// Creates a stream of values over `slice` with the given configuration
+    pub fn with_config(slice: &'a [u8], config: Config) -> Self {
+        StreamDeserializer {
+            de: Deserializer::with_config(slice, config),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of bytes of the slice consumed so far
+    pub fn offset(&self) -> usize {
+        self.de.offset()
+    }
+
+    /// Skips whitespace and record separators and returns a peek into the next character
+    fn parse_separators(&mut self) -> Option<u8> {
+        loop {
+            match self.de.parse_whitespace() {
+                Some(RS) => self.de.eat_char(),
+                other => return other,
+            }
+        }
+    }
+
+    /// Moves past the first newline or record separator that follows `start`
+    fn recover(&mut self, start: usize) {
+        let slice = self.de.slice;
+        self.de.index = slice[start..]
+            .iter()
+            .position(|&b| b == b'\n' || b == RS)
+            .map(|i| start + i + 1)
+            .unwrap_or_else(|| slice.len());
+        self.de.depth = 0;
+
+        #[cfg(feature = "error-path")]
+        {
+            self.de.path = crate::de::path::PathStack::new();
+        }
+    }
+}
+
+impl<'a, T> Iterator for StreamDeserializer<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    type Item = Result<(T, Range<usize>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parse_separators()?;
+
+        let start = self.de.index;
+        match de::Deserialize::deserialize(&mut self.de) {
+            Ok(value) => Some(Ok((value, start..self.de.index))),
+            Err(e) => {
+                self.recover(start);
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_derive::Deserialize;
+
+    use crate::de::{Error, StreamDeserializer};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Log<'a> {
+        level: u8,
+        msg: &'a str,
+    }
+
+    #[test]
+    fn concatenated() {
+        let mut values = StreamDeserializer::<[u8; 2]>::new(b" [1, 2][3, 4]  [5, 6] ");
+        assert_eq!(values.next(), Some(Ok(([1, 2], 1..7))));
+        assert_eq!(values.next(), Some(Ok(([3, 4], 7..13))));
+        assert_eq!(values.next(), Some(Ok(([5, 6], 15..21))));
+        assert_eq!(values.next(), None);
+        assert_eq!(values.offset(), 22);
+    }
+
+    #[test]
+    fn ndjson() {
+        let input = b"{\"level\":1,\"msg\":\"boot\"}\r\n{\"level\":2,\"msg\":\"wifi\"}\n";
+        let mut values = StreamDeserializer::<Log<'_>>::new(input);
+        assert_eq!(
+            values.next(),
+            Some(Ok((
+                Log {
+                    level: 1,
+                    msg: "boot"
+                },
+                0..24
+            )))
+        );
+        assert_eq!(
+            values.next(),
+            Some(Ok((
+                Log {
+                    level: 2,
+                    msg: "wifi"
+                },
+                26..50
+            )))
+        );
+        assert_eq!(values.next(), None);
+    }
+
+    #[test]
+    fn json_text_sequence() {
+        let input = b"\x1e{\"level\":1,\"msg\":\"boot\"}\n\x1e{\"level\":2,\"msg\":\"wifi\"}\n";
+        let values = StreamDeserializer::<Log<'_>>::new(input);
+        assert_eq!(values.map(|value| value.unwrap().0.level).sum::<u8>(), 3);
+    }
+
+    #[test]
+    fn errors_do_not_abort() {
+        let input = b"{\"level\":1,\"msg\":\"boot\"}\n{\"level\":1,\"msg\":\n\x1e{\"level\":300}\n{\"level\":3,\"msg\":\"ok\"}";
+        let mut values = StreamDeserializer::<Log<'_>>::new(input);
+        assert!(values.next().unwrap().is_ok());
+        assert_eq!(values.next(), Some(Err(Error::InvalidType)));
+        assert_eq!(values.next(), Some(Err(Error::InvalidNumber)));
+        assert_eq!(
+            values.next(),
+            Some(Ok((
+                Log {
+                    level: 3,
+                    msg: "ok"
+                },
+                58..80
+            )))
+        );
+        assert_eq!(values.next(), None);
+    }
+}
@@ -0,0 +1,378 @@
+use heapless::{consts::U32, Vec};
+use serde::de;
+
+use crate::de::{from_slice, Error, Reader, Result, Token};
+
+/// The type of a `TapeToken`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenKind {
+    /// An object
+    Object,
+    /// An array
+    Array,
+    /// A string, or an object key
+    String,
+    /// A number
+    Number,
+    /// `true` or `false`
+    Bool,
+    /// `null`
+    Null,
+}
+
+/// A compact token describing one JSON value, as written by `parse_tape`
+///
+/// The values of a container follow its token on the tape; each member of an object takes up
+/// a `String` token for the key followed by the value's tokens.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TapeToken {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+    size: usize,
+    next_sibling: usize,
+}
+
+impl TapeToken {
+    /// Returns the type of the value
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    /// Returns the byte offset of the start of the value. For strings it excludes the opening
+    /// quote.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the byte offset of the end of the value. For strings it excludes the closing quote.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Returns the number of members of an object or elements of an array; `0` for other values
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the index of the token that follows this value and all of its contents
+    pub fn next_sibling(&self) -> usize {
+        self.next_sibling
+    }
+}
+
+impl Default for TapeToken {
+    fn default() -> Self {
+        TapeToken {
+            kind: TokenKind::Null,
+            start: 0,
+            end: 0,
+            size: 0,
+            next_sibling: 0,
+        }
+    }
+}
+
+/// Parses `input` into `tokens` and returns the number of tokens written
+///
+/// The document can be nested up to 32 levels deep. `Error::TapeFull` is returned if `tokens` is
+/// too short to hold all of the document's tokens.
+///
+/// ```
+/// use serde_json_core::de::{parse_tape, Tape, TapeToken};
+///
+/// let input = br#"{"led": {"color": [255, 0, 0], "on": true}}"#;
+/// let mut tokens = [TapeToken::default(); 16];
+/// let n = parse_tape(input, &mut tokens).unwrap();
+///
+/// let led = Tape::new(input, &tokens[..n]).root().unwrap().get("led").unwrap();
+/// assert_eq!(led.get("on").unwrap().as_bool(), Some(true));
+/// assert_eq!(led.get("color").unwrap().get_index(0).unwrap().deserialize(), Ok(255u8));
+/// ```
+pub fn parse_tape(input: &[u8], tokens: &mut [TapeToken]) -> Result<usize> {
+    // index of the token of each open container
+    let mut open: Vec<usize, U32> = Vec::new();
+    let mut len = 0;
+    let mut reader = Reader::new(input);
+
+    while let Some(token) = reader.next() {
+        let token = token?;
+        let end = reader.offset();
+
+        let (kind, start, end) = match token {
+            Token::EndObject | Token::EndArray => {
+                let index = open.pop().ok_or(Error::ExpectedSomeValue)?;
+                tokens[index].end = end;
+                tokens[index].next_sibling = len;
+                continue;
+            }
+            Token::BeginObject => (TokenKind::Object, end - 1, 0),
+            Token::BeginArray => (TokenKind::Array, end - 1, 0),
+            Token::Key(s) | Token::String(s) => {
+                let start = offset_of(input, s);
+                (TokenKind::String, start, start + s.len())
+            }
+            Token::Number(s) => (TokenKind::Number, offset_of(input, s), end),
+            Token::Bool(true) => (TokenKind::Bool, end - 4, end),
+            Token::Bool(false) => (TokenKind::Bool, end - 5, end),
+            Token::Null => (TokenKind::Null, end - 4, end),
+        };
+
+        // objects count their members at the key, arrays their elements at the value
+        if let Some(&parent) = open.last() {
+            match (token, tokens[parent].kind) {
+                (Token::Key(_), _) | (_, TokenKind::Array) => tokens[parent].size += 1,
+                _ => {}
+            }
+        }
+
+        let slot = tokens.get_mut(len).ok_or(Error::TapeFull)?;
+        *slot = TapeToken {
+            kind,
+            start,
+            end,
+            size: 0,
+            next_sibling: len + 1,
+        };
+
+        match token {
+            Token::BeginObject | Token::BeginArray => {
+                open.push(len).map_err(|_| Error::DepthLimitExceeded)?;
+            }
+            _ => {}
+        }
+        len += 1;
+    }
+
+    Ok(len)
+}
+
+fn offset_of(input: &[u8], s: &str) -> usize {
+    s.as_ptr() as usize - input.as_ptr() as usize
+}
+
+/// Random access to a document parsed with `parse_tape`
+///
+/// The tokens are not checked against the input. If they weren't written by `parse_tape` for this
+/// input, the accessors return `None` (or an error) where a token or a span is out of bounds, and
+/// may return unrelated values otherwise, but they don't panic.
+#[derive(Clone, Copy, Debug)]
+pub struct Tape<'a, 't> {
+    input: &'a [u8],
+    tokens: &'t [TapeToken],
+}
+
+impl<'a, 't> Tape<'a, 't> {
+    /// Creates a view of the document `input` given the tokens `parse_tape` wrote for it
+    pub fn new(input: &'a [u8], tokens: &'t [TapeToken]) -> Self {
+        Tape { input, tokens }
+    }
+
+    /// Returns the top-level value, or `None` if the tape is empty
+    pub fn root(&self) -> Option<TapeValue<'a, 't>> {
+        self.value(0)
+    }
+
+    fn value(&self, index: usize) -> Option<TapeValue<'a, 't>> {
+        self.tokens.get(index).map(|token| TapeValue {
+            tape: *self,
+            token,
+            index,
+        })
+    }
+}
+
+/// A value of a `Tape`
+#[derive(Clone, Copy, Debug)]
+pub struct TapeValue<'a, 't> {
+    tape: Tape<'a, 't>,
+    token: &'t TapeToken,
+    index: usize,
+}
+
+impl<'a, 't> TapeValue<'a, 't> {
+    /// Returns the token of this value
+    pub fn token(&self) -> &'t TapeToken {
+        self.token
+    }
+
+    /// Returns the type of this value
+    pub fn kind(&self) -> TokenKind {
+        self.token().kind
+    }
+
+    /// Returns the bytes of this value. Strings don't include their quotes.
+    ///
+    /// Returns `None` if the token's span is not within the input.
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        self.tape.input.get(self.token.start..self.token.end)
+    }
+
+    /// Returns the contents of a string, with escape sequences left as is
+    pub fn as_str(&self) -> Option<&'a str> {
+        if self.kind() == TokenKind::String {
+            // the reader already checked that strings are valid UTF-8
+            core::str::from_utf8(self.as_bytes()?).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.kind() {
+            TokenKind::Bool => Some(self.as_bytes()? == b"true"),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this value is `null`
+    pub fn is_null(&self) -> bool {
+        self.kind() == TokenKind::Null
+    }
+
+    /// Looks up the value of `key` in an object
+    pub fn get(&self, key: &str) -> Option<TapeValue<'a, 't>> {
+        if self.kind() != TokenKind::Object {
+            return None;
+        }
+
+        let mut index = self.index + 1;
+        for _ in 0..self.token.size {
+            let key_token = self.tape.value(index)?;
+            let value = self.tape.value(key_token.token.next_sibling)?;
+            if key_token.as_bytes()? == key.as_bytes() {
+                return Some(value);
+            }
+            index = value.token.next_sibling;
+        }
+
+        None
+    }
+
+    /// Returns the `i`-th element of an array
+    pub fn get_index(&self, i: usize) -> Option<TapeValue<'a, 't>> {
+        if self.kind() != TokenKind::Array || i >= self.token.size {
+            return None;
+        }
+
+        let mut index = self.index + 1;
+        for _ in 0..i {
+            index = self.tape.tokens.get(index)?.next_sibling;
+        }
+
+        self.tape.value(index)
+    }
+
+    /// Deserializes this value as a `T`
+    ///
+    /// `Error::EofWhileParsingValue` is returned if the token's span is not within the input.
+    pub fn deserialize<T>(&self) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        let token = self.token;
+        let (start, end) = match token.kind {
+            // include the quotes
+            TokenKind::String => (token.start.wrapping_sub(1), token.end.wrapping_add(1)),
+            _ => (token.start, token.end),
+        };
+
+        from_slice(
+            self.tape
+                .input
+                .get(start..end)
+                .ok_or(Error::EofWhileParsingValue)?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::de::{parse_tape, Error, Tape, TapeToken, TokenKind};
+
+    #[test]
+    fn layout() {
+        let input = br#"{"a": [1, "x"], "b": null}"#;
+        let mut tokens = [TapeToken::default(); 8];
+        assert_eq!(parse_tape(input, &mut tokens), Ok(7));
+
+        let summary = |t: &TapeToken| (t.kind(), t.start(), t.end(), t.size(), t.next_sibling());
+        let expected = [
+            (TokenKind::Object, 0, 26, 2, 7),
+            (TokenKind::String, 2, 3, 0, 2),
+            (TokenKind::Array, 6, 14, 2, 5),
+            (TokenKind::Number, 7, 8, 0, 4),
+            (TokenKind::String, 11, 12, 0, 5),
+            (TokenKind::String, 17, 18, 0, 6),
+            (TokenKind::Null, 21, 25, 0, 7),
+        ];
+        for (token, expected) in tokens.iter().zip(expected.iter()) {
+            assert_eq!(summary(token), *expected);
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        let input =
+            br#" {"name": "dock", "sensors": [{"id": 1}, {"id": 2, "on": false}], "x": 1.5} "#;
+        let mut tokens = [TapeToken::default(); 32];
+        let n = parse_tape(input, &mut tokens).unwrap();
+        let root = Tape::new(input, &tokens[..n]).root().unwrap();
+
+        assert_eq!(root.get("name").unwrap().as_str(), Some("dock"));
+        assert_eq!(root.get("name").unwrap().deserialize(), Ok("dock"));
+        assert_eq!(root.get("x").unwrap().deserialize(), Ok(1.5f32));
+        assert!(root.get("missing").is_none());
+
+        let sensors = root.get("sensors").unwrap();
+        assert_eq!(sensors.token().size(), 2);
+        let second = sensors.get_index(1).unwrap();
+        assert_eq!(second.get("id").unwrap().deserialize(), Ok(2u8));
+        assert_eq!(second.get("on").unwrap().as_bool(), Some(false));
+        assert!(sensors.get_index(2).is_none());
+        assert!(sensors.get("id").is_none());
+    }
+
+    #[test]
+    fn mismatched_tokens() {
+        let input = br#"{"a": [1, "x"]}"#;
+        let mut tokens = [TapeToken::default(); 8];
+        let n = parse_tape(input, &mut tokens).unwrap();
+
+        assert!(Tape::new(input, &[]).root().is_none());
+
+        // the tokens of a longer document
+        let root = Tape::new(b"{}", &tokens[..n]).root().unwrap();
+        assert_eq!(root.as_bytes(), None);
+        assert_eq!(root.deserialize::<()>(), Err(Error::EofWhileParsingValue));
+        assert!(root.get("a").is_none());
+
+        // a truncated tape
+        let root = Tape::new(input, &tokens[..3]).root().unwrap();
+        let a = root.get("a").unwrap();
+        assert!(a.get_index(0).is_none());
+
+        // tokens that don't come from `parse_tape`
+        let tokens = [TapeToken::default(); 2];
+        let root = Tape::new(input, &tokens).root().unwrap();
+        assert_eq!(root.as_str(), None);
+        assert!(root.is_null());
+    }
+
+    #[test]
+    fn errors() {
+        let mut tokens = [TapeToken::default(); 3];
+        assert_eq!(parse_tape(b"[1, 2, 3]", &mut tokens), Err(Error::TapeFull));
+        assert_eq!(
+            parse_tape(b"[1, 2", &mut tokens),
+            Err(Error::EofWhileParsingList)
+        );
+        assert_eq!(
+            parse_tape(b"[1, 2] 3", &mut tokens),
+            Err(Error::TrailingCharacters)
+        );
+        assert_eq!(parse_tape(b"[1-2]", &mut tokens), Err(Error::InvalidNumber));
+        assert_eq!(parse_tape(b"1.2.3", &mut tokens), Err(Error::InvalidNumber));
+    }
+}
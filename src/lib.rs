@@ -55,7 +55,7 @@
 //!
 //! - Anything that involves dynamic memory allocation
 //!   - Like the dynamic [`Value`](https://docs.rs/serde_json/1.0.11/serde_json/enum.Value.html)
-//!     type
+//!     type. A fixed-capacity [`Value`](value/struct.Value.html) is provided instead.
 //!
 //! # MSRV
 //!
@@ -68,13 +68,18 @@
 #![deny(warnings)]
 #![cfg_attr(not(feature = "std"), no_std)]
 
+mod config;
 pub mod de;
 pub mod ser;
+pub mod value;
 
+pub use self::config::Config;
 #[doc(inline)]
 pub use self::de::{from_slice, from_str};
 #[doc(inline)]
 pub use self::ser::{to_string, to_vec};
+#[doc(inline)]
+pub use self::value::Value;
 
 #[allow(deprecated)]
 unsafe fn uninitialized<T>() -> T {
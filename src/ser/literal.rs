@@ -0,0 +1,282 @@
+use core::mem;
+
+use serde::ser::{self, Serialize};
+
+use heapless::{ArrayLength, Vec};
+
+use crate::ser::{Error, Result, Serializer};
+
+/// Writes a JSON literal, with interpolated `Serialize` values, to the end of a `heapless::Vec<u8, N>`
+///
+/// Object keys are string literals or parenthesized `&str` expressions. Values are `null`, `true`,
+/// `false`, nested arrays and objects, or any expression whose type implements `Serialize`. The
+/// writes are generated at compile time, so nothing is parsed at run time.
+///
+/// Evaluates to a `ser::Result<usize>` holding the number of bytes written. If the literal doesn't
+/// fit, `Error::BufferFull` is returned and the buffer is left as it was.
+///
+/// Interpolated expressions are evaluated in order in the calling function, so `?` and `return` in
+/// them apply to that function; what the literal had written by then is rolled back. All of them
+/// are evaluated, even after a write has failed.
+///
+/// ```
+/// use heapless::{consts::U64, Vec};
+/// use serde_json_core::json;
+///
+/// let (temp, channel) = (21, "wifi");
+///
+/// let mut buf = Vec::<u8, U64>::new();
+/// let len = json!(buf, {"temp": temp * 2, "ok": true, (channel): [1, null]}).unwrap();
+/// assert_eq!(&buf[..], br#"{"temp":42,"ok":true,"wifi":[1,null]}"#);
+/// assert_eq!(len, buf.len());
+/// ```
+///
+/// Long literals may need a higher `#![recursion_limit]`.
+#[macro_export]
+macro_rules! json {
+    ($buf:expr, $($json:tt)+) => {{
+        let mut writer = $crate::ser::Literal::new(&mut $buf);
+        $crate::json_internal!(writer, $($json)+);
+        writer.finish()
+    }};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    // The elements of an array. Tokens are accumulated in parentheses until the next comma, and the
+    // statements that write the separator before the next element are kept in brackets.
+    (@array $w:ident [$($sep:tt)*] ()) => {};
+    (@array $w:ident [$($sep:tt)*] ($($elem:tt)+)) => {
+        $($sep)*
+        $crate::json_internal!($w, $($elem)+);
+    };
+    (@array $w:ident [$($sep:tt)*] ($($elem:tt)+) , $($rest:tt)*) => {
+        $($sep)*
+        $crate::json_internal!($w, $($elem)+);
+        $crate::json_internal!(@array $w [$w.raw(b",");] () $($rest)*);
+    };
+    (@array $w:ident [$($sep:tt)*] ($($elem:tt)*) $next:tt $($rest:tt)*) => {
+        $crate::json_internal!(@array $w [$($sep)*] ($($elem)* $next) $($rest)*);
+    };
+
+    // The members of an object
+    (@object $w:ident [$($sep:tt)*]) => {};
+    (@object $w:ident [$($sep:tt)*] $key:tt : $($rest:tt)*) => {
+        $($sep)*
+        $w.key($key);
+        $crate::json_internal!(@member $w () $($rest)*);
+    };
+
+    // The value of an object member
+    (@member $w:ident ($($value:tt)+)) => {
+        $crate::json_internal!($w, $($value)+);
+    };
+    (@member $w:ident ($($value:tt)+) , $($rest:tt)*) => {
+        $crate::json_internal!($w, $($value)+);
+        $crate::json_internal!(@object $w [$w.raw(b",");] $($rest)*);
+    };
+    (@member $w:ident ($($value:tt)*) $next:tt $($rest:tt)*) => {
+        $crate::json_internal!(@member $w ($($value)* $next) $($rest)*);
+    };
+
+    // A single value
+    ($w:ident, null) => {
+        $w.raw(b"null");
+    };
+    ($w:ident, true) => {
+        $w.raw(b"true");
+    };
+    ($w:ident, false) => {
+        $w.raw(b"false");
+    };
+    ($w:ident, [$($elems:tt)*]) => {
+        $w.raw(b"[");
+        $crate::json_internal!(@array $w [] () $($elems)*);
+        $w.raw(b"]");
+    };
+    ($w:ident, {$($members:tt)*}) => {
+        $w.raw(b"{");
+        $crate::json_internal!(@object $w [] $($members)*);
+        $w.raw(b"}");
+    };
+    ($w:ident, $value:expr) => {
+        $w.value(&$value);
+    };
+}
+
+/// The writes generated by `json!`
+///
+/// The buffer is moved into a `Serializer` for the duration of the macro, and moved back when this
+/// is dropped. The first error is kept and the writes after it are skipped, so that the macro
+/// doesn't need `?`, which would apply to the caller's function.
+#[doc(hidden)]
+pub struct Literal<'a, B>
+where
+    B: ArrayLength<u8>,
+{
+    buf: &'a mut Vec<u8, B>,
+    ser: Serializer<B>,
+    start: usize,
+    /// The first write that failed
+    error: Option<Error>,
+    /// The writes are kept when this is dropped
+    done: bool,
+}
+
+impl<'a, B> Literal<'a, B>
+where
+    B: ArrayLength<u8>,
+{
+    pub fn new(buf: &'a mut Vec<u8, B>) -> Self {
+        let mut ser = Serializer::new();
+        let start = buf.len();
+        mem::swap(buf, &mut ser.buf);
+        Literal {
+            buf,
+            ser,
+            start,
+            error: None,
+            done: false,
+        }
+    }
+
+    pub fn raw(&mut self, json: &[u8]) {
+        self.write(|ser| {
+            ser.buf.extend_from_slice(json)?;
+            Ok(())
+        })
+    }
+
+    pub fn key(&mut self, key: &str) {
+        self.write(|ser| {
+            key.serialize(&mut *ser)?;
+            ser.buf.push(b':')?;
+            Ok(())
+        })
+    }
+
+    pub fn value<T>(&mut self, value: &T)
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        self.write(|ser| value.serialize(ser))
+    }
+
+    /// Runs `f` unless an earlier write failed, and keeps its error
+    fn write<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Serializer<B>) -> Result<()>,
+    {
+        if self.error.is_none() {
+            self.error = f(&mut self.ser).err();
+        }
+    }
+
+    /// Returns how many bytes were written, or the error of the write that failed
+    ///
+    /// The writes are rolled back on error, and also if this is dropped without being finished,
+    /// like when an interpolated expression returns early.
+    pub fn finish(mut self) -> Result<usize> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => {
+                self.done = true;
+                Ok(self.ser.buf.len() - self.start)
+            }
+        }
+    }
+}
+
+impl<'a, B> Drop for Literal<'a, B>
+where
+    B: ArrayLength<u8>,
+{
+    fn drop(&mut self) {
+        if !self.done {
+            self.ser.truncate(self.start);
+        }
+        mem::swap(self.buf, &mut self.ser.buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_derive::Serialize;
+
+    use heapless::{
+        consts::{U128, U16, U64},
+        Vec,
+    };
+
+    use crate::ser::Error;
+
+    #[derive(Serialize)]
+    struct Led {
+        on: bool,
+    }
+
+    #[test]
+    fn values() {
+        let mut buf = Vec::<u8, U64>::new();
+        assert_eq!(json!(buf, null), Ok(4));
+        assert_eq!(&buf[..], b"null");
+
+        let mut buf = Vec::<u8, U128>::new();
+        let channels = [1u8, 6, 11];
+        let name = "id";
+        let len = json!(buf, [
+            -1,
+            channels.len() * 2,
+            Led { on: true },
+            [],
+            {},
+            [[true], {"a": false,}],
+            {(name): channels, "b": "x",},
+        ])
+        .unwrap();
+        assert_eq!(
+            &buf[..],
+            &br#"[-1,6,{"on":true},[],{},[[true],{"a":false}],{"id":[1,6,11],"b":"x"}]"#[..]
+        );
+        assert_eq!(len, buf.len());
+    }
+
+    #[test]
+    fn appends() {
+        let mut buf = Vec::<u8, U16>::new();
+        assert_eq!(json!(buf, {"a": 1}), Ok(7));
+        assert_eq!(json!(buf, [true]), Ok(6));
+        assert_eq!(&buf[..], br#"{"a":1}[true]"#);
+    }
+
+    #[test]
+    fn rollback() {
+        let mut buf = Vec::<u8, U16>::new();
+        buf.extend_from_slice(b"1 ").unwrap();
+        assert_eq!(json!(buf, {"a": "does not fit"}), Err(Error::BufferFull));
+        assert_eq!(&buf[..], b"1 ");
+    }
+
+    #[test]
+    fn caller_control_flow() {
+        // `?` and `return` in interpolated expressions leave the calling function, not the macro
+        fn first(channels: &[u8], buf: &mut Vec<u8, U16>) -> Option<usize> {
+            json!(*buf, {"first": *channels.first()?}).ok()
+        }
+
+        fn checked(value: i8, buf: &mut Vec<u8, U16>) -> &'static str {
+            let len = json!(*buf, [if value < 0 { return "negative" } else { value }]);
+            assert_eq!(len, Ok(3));
+            "written"
+        }
+
+        let mut buf = Vec::<u8, U16>::new();
+        assert_eq!(first(&[], &mut buf), None);
+        assert_eq!(&buf[..], b"");
+        assert_eq!(first(&[6, 11], &mut buf), Some(11));
+        assert_eq!(checked(-1, &mut buf), "negative");
+        assert_eq!(checked(1, &mut buf), "written");
+        assert_eq!(&buf[..], br#"{"first":6}[1]"#);
+    }
+}
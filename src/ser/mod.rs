@@ -10,18 +10,42 @@ use self::map::SerializeMap;
 use self::seq::SerializeSeq;
 use self::struct_::SerializeStruct;
 
+#[doc(hidden)]
+pub use self::literal::Literal;
+pub use self::records::{RecordFormat, RecordWriter};
+pub use self::writer::JsonWriter;
+
+mod literal;
 mod map;
+mod records;
 mod seq;
 mod struct_;
+mod writer;
 
 /// Serialization result
 pub type Result<T> = ::core::result::Result<T, Error>;
 
 /// This type represents all possible errors that can occur when serializing JSON data
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+// `#[non_exhaustive]` is newer than our MSRV
+#[allow(clippy::manual_non_exhaustive)]
 pub enum Error {
     /// Buffer is full
     BufferFull,
+
+    /// Objects and arrays are nested deeper than `JsonWriter` supports
+    DepthLimitExceeded,
+
+    /// The value is of a type this serializer doesn't support
+    UnsupportedType,
+
+    /// Error with a custom message that we had to discard.
+    Custom,
+
+    /// Error with a custom message that was preserved.
+    #[cfg(feature = "custom-error-messages")]
+    CustomWithMessage(String<U64>),
+
     #[doc(hidden)]
     __Extensible,
 }
@@ -47,23 +71,109 @@ impl ::std::error::Error for Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Buffer is full")
+        write!(
+            f,
+            "{}",
+            match self {
+                Error::BufferFull => "Buffer is full",
+                Error::DepthLimitExceeded => "Objects and arrays are nested too deeply",
+                Error::UnsupportedType => "Unsupported type",
+                Error::Custom => "Custom error",
+                #[cfg(feature = "custom-error-messages")]
+                Error::CustomWithMessage(msg) => msg.as_str(),
+                _ => "Unknown error",
+            }
+        )
     }
 }
 
-pub(crate) struct Serializer<B>
+/// A structure for serializing Rust values into JSON
+///
+/// Values are appended to an internal buffer of capacity `B`, so several values can be serialized
+/// back to back before taking the buffer out with `into_inner`.
+pub struct Serializer<B>
 where
     B: heapless::ArrayLength<u8>,
 {
     buf: Vec<u8, B>,
+    /// Strings are written verbatim, for `value::RawValue`
+    raw: bool,
+    /// Control characters in strings are escaped, for `RecordWriter`
+    escape_control: bool,
 }
 
 impl<B> Serializer<B>
 where
     B: heapless::ArrayLength<u8>,
 {
-    fn new() -> Self {
-        Serializer { buf: Vec::new() }
+    /// Creates a new JSON serializer
+    pub fn new() -> Self {
+        Serializer {
+            buf: Vec::new(),
+            raw: false,
+            escape_control: false,
+        }
+    }
+
+    /// Returns the JSON bytes written so far
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Discards everything written after the first `len` bytes
+    pub(crate) fn truncate(&mut self, len: usize) {
+        // NOTE(pop) `Vec::truncate` (and `Vec::clear`) index out of bounds in heapless v0.5
+        while self.buf.len() > len {
+            self.buf.pop();
+        }
+    }
+
+    /// Consumes the serializer and returns the buffer holding the serialized JSON
+    pub fn into_inner(self) -> Vec<u8, B> {
+        self.buf
+    }
+
+    /// Writes the contents of a string with its control characters escaped
+    ///
+    /// Quotes and backslashes are left alone, like everywhere else, so that strings borrowed from
+    /// the deserializer, which keep their escape sequences, aren't escaped twice.
+    fn escape_control_chars(&mut self, v: &str) -> Result<()> {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+
+        let bytes = v.as_bytes();
+        let mut start = 0;
+        let mut unicode = *b"\\u0000";
+        for (i, &c) in bytes.iter().enumerate() {
+            let escape: &[u8] = match c {
+                b'\n' => b"\\n",
+                b'\r' => b"\\r",
+                b'\t' => b"\\t",
+                0x08 => b"\\b",
+                0x0c => b"\\f",
+                0x00..=0x1f => {
+                    unicode[4] = HEX[usize::from(c >> 4)];
+                    unicode[5] = HEX[usize::from(c & 0xf)];
+                    &unicode
+                }
+                _ => continue,
+            };
+
+            self.buf.extend_from_slice(&bytes[start..i])?;
+            self.buf.extend_from_slice(escape)?;
+            start = i + 1;
+        }
+
+        self.buf.extend_from_slice(&bytes[start..])?;
+        Ok(())
+    }
+}
+
+impl<B> Default for Serializer<B>
+where
+    B: heapless::ArrayLength<u8>,
+{
+    fn default() -> Self {
+        Serializer::new()
     }
 }
 
@@ -94,6 +204,7 @@ macro_rules! serialize_unsigned {
 macro_rules! serialize_signed {
     ($self:ident, $N:expr, $v:expr, $ixx:ident, $uxx:ident) => {{
         let v = $v;
+        #[allow(clippy::legacy_numeric_constants)]
         let (signed, mut v) = if v == $ixx::min_value() {
             (true, $ixx::max_value() as $uxx + 1)
         } else if v < 0 {
@@ -134,6 +245,8 @@ macro_rules! serialize_fmt {
     }};
 }
 
+// `T: ?Sized` is declared where serde's trait declares it
+#[allow(clippy::multiple_bound_locations)]
 impl<'a, B> ser::Serializer for &'a mut Serializer<B>
 where
     B: heapless::ArrayLength<u8>,
@@ -207,18 +320,27 @@ where
     }
 
     fn serialize_char(self, _v: char) -> Result<Self::Ok> {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        if self.raw {
+            self.buf.extend_from_slice(v.as_bytes())?;
+            return Ok(());
+        }
+
         self.buf.push(b'"')?;
-        self.buf.extend_from_slice(v.as_bytes())?;
+        if self.escape_control {
+            self.escape_control_chars(v)?;
+        } else {
+            self.buf.extend_from_slice(v.as_bytes())?;
+        }
         self.buf.push(b'"')?;
         Ok(())
     }
 
     fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok> {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
@@ -234,11 +356,11 @@ where
     }
 
     fn serialize_unit(self) -> Result<Self::Ok> {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn serialize_unit_variant(
@@ -250,15 +372,19 @@ where
         self.serialize_str(variant)
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
-        self,
-        _name: &'static str,
-        _value: &T,
-    ) -> Result<Self::Ok>
+    /// Unsupported, except for `value::RawValue`
+    fn serialize_newtype_struct<T: ?Sized>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ser::Serialize,
     {
-        unreachable!()
+        if name != crate::value::RAW_VALUE_TOKEN {
+            return Err(Error::UnsupportedType);
+        }
+
+        self.raw = true;
+        let result = value.serialize(&mut *self);
+        self.raw = false;
+        result
     }
 
     fn serialize_newtype_variant<T: ?Sized>(
@@ -271,7 +397,7 @@ where
     where
         T: ser::Serialize,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
@@ -289,7 +415,7 @@ where
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn serialize_tuple_variant(
@@ -299,7 +425,7 @@ where
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
@@ -321,14 +447,14 @@ where
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 
     fn collect_str<T: ?Sized>(self, _value: &T) -> Result<Self::Ok>
     where
         T: fmt::Display,
     {
-        unreachable!()
+        Err(Error::UnsupportedType)
     }
 }
 
@@ -340,7 +466,7 @@ where
 {
     let mut ser = Serializer::new();
     value.serialize(&mut ser)?;
-    Ok(unsafe { String::from_utf8_unchecked(ser.buf) })
+    Ok(unsafe { String::from_utf8_unchecked(ser.into_inner()) })
 }
 
 /// Serializes the given data structure as a JSON byte vector
@@ -351,30 +477,43 @@ where
 {
     let mut ser = Serializer::new();
     value.serialize(&mut ser)?;
-    Ok(ser.buf)
+    Ok(ser.into_inner())
 }
 
 impl ser::Error for Error {
-    fn custom<T>(_msg: T) -> Self
+    #[cfg_attr(not(feature = "custom-error-messages"), allow(unused_variables))]
+    fn custom<T>(msg: T) -> Self
     where
         T: fmt::Display,
     {
-        unreachable!()
+        #[cfg(not(feature = "custom-error-messages"))]
+        {
+            Error::Custom
+        }
+        #[cfg(feature = "custom-error-messages")]
+        {
+            let mut string = String::new();
+            // NOTE a `Display` implementation that ignores the precision may not fit in `string`;
+            // in that case we keep whatever was written before it filled up
+            write!(string, "{:.64}", msg).ok();
+            Error::CustomWithMessage(string)
+        }
     }
 }
 
-pub(crate) enum Unreachable {}
+/// An uninhabited type standing in for the compound serializers this crate doesn't support
+pub enum Unreachable {}
 
 impl ser::SerializeTupleStruct for Unreachable {
     type Ok = ();
     type Error = Error;
 
     fn serialize_field<T: ?Sized>(&mut self, _value: &T) -> Result<()> {
-        unreachable!()
+        match *self {}
     }
 
     fn end(self) -> Result<Self::Ok> {
-        unreachable!()
+        match self {}
     }
 }
 
@@ -383,14 +522,16 @@ impl ser::SerializeTupleVariant for Unreachable {
     type Error = Error;
 
     fn serialize_field<T: ?Sized>(&mut self, _value: &T) -> Result<()> {
-        unreachable!()
+        match *self {}
     }
 
     fn end(self) -> Result<Self::Ok> {
-        unreachable!()
+        match self {}
     }
 }
 
+// `T: ?Sized` is declared where serde's trait declares it
+#[allow(clippy::multiple_bound_locations)]
 impl ser::SerializeMap for Unreachable {
     type Ok = ();
     type Error = Error;
@@ -399,21 +540,23 @@ impl ser::SerializeMap for Unreachable {
     where
         T: ser::Serialize,
     {
-        unreachable!()
+        match *self {}
     }
 
     fn serialize_value<T: ?Sized>(&mut self, _value: &T) -> Result<()>
     where
         T: ser::Serialize,
     {
-        unreachable!()
+        match *self {}
     }
 
     fn end(self) -> Result<Self::Ok> {
-        unreachable!()
+        match self {}
     }
 }
 
+// `T: ?Sized` is declared where serde's trait declares it
+#[allow(clippy::multiple_bound_locations)]
 impl ser::SerializeStructVariant for Unreachable {
     type Ok = ();
     type Error = Error;
@@ -422,16 +565,17 @@ impl ser::SerializeStructVariant for Unreachable {
     where
         T: ser::Serialize,
     {
-        unreachable!()
+        match *self {}
     }
 
     fn end(self) -> Result<Self::Ok> {
-        unreachable!()
+        match self {}
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use serde::ser;
     use serde_derive::Serialize;
 
     use heapless::consts::U128;
@@ -516,6 +660,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::excessive_precision)]
     fn struct_f32() {
         #[derive(Serialize)]
         struct Temperature {
@@ -597,4 +742,122 @@ mod tests {
             r#"{"a":true,"b":false}"#
         );
     }
+
+    #[test]
+    fn multiple_values() {
+        use serde::Serialize;
+
+        let mut ser = crate::ser::Serializer::<N>::new();
+        true.serialize(&mut ser).unwrap();
+        [1, 2].serialize(&mut ser).unwrap();
+        "three".serialize(&mut ser).unwrap();
+
+        assert_eq!(ser.as_bytes(), br#"true[1,2]"three""#);
+        assert_eq!(&ser.into_inner()[..], br#"true[1,2]"three""#);
+    }
+
+    #[test]
+    fn unsupported_types() {
+        use crate::ser::Error;
+
+        #[derive(Serialize)]
+        struct Unit;
+
+        #[derive(Serialize)]
+        struct Newtype(u8);
+
+        #[derive(Serialize)]
+        struct Tuple(u8, u8);
+
+        #[derive(Serialize)]
+        enum Enum {
+            Newtype(u8),
+            Tuple(u8, u8),
+            Struct { a: u8 },
+        }
+
+        assert_eq!(crate::to_string::<N, _>(&'c'), Err(Error::UnsupportedType));
+        assert_eq!(crate::to_string::<N, _>(&()), Err(Error::UnsupportedType));
+        assert_eq!(crate::to_string::<N, _>(&Unit), Err(Error::UnsupportedType));
+        assert_eq!(
+            crate::to_string::<N, _>(&Newtype(0)),
+            Err(Error::UnsupportedType)
+        );
+        assert_eq!(
+            crate::to_string::<N, _>(&Tuple(0, 1)),
+            Err(Error::UnsupportedType)
+        );
+        assert_eq!(
+            crate::to_string::<N, _>(&Enum::Newtype(0)),
+            Err(Error::UnsupportedType)
+        );
+        assert_eq!(
+            crate::to_string::<N, _>(&Enum::Tuple(0, 1)),
+            Err(Error::UnsupportedType)
+        );
+        assert_eq!(
+            crate::to_string::<N, _>(&Enum::Struct { a: 0 }),
+            Err(Error::UnsupportedType)
+        );
+        assert_eq!(
+            ser::Serializer::serialize_bytes(&mut crate::ser::Serializer::<N>::new(), b"bytes"),
+            Err(Error::UnsupportedType)
+        );
+        assert_eq!(
+            ser::Serializer::collect_str(&mut crate::ser::Serializer::<N>::new(), &0),
+            Err(Error::UnsupportedType)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "custom-error-messages"))]
+    fn custom_error() {
+        struct Invalid;
+
+        impl ser::Serialize for Invalid {
+            fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ser::Serializer,
+            {
+                Err(ser::Error::custom("invalid value"))
+            }
+        }
+
+        assert_eq!(
+            crate::to_string::<N, _>(&[Invalid]),
+            Err(crate::ser::Error::Custom)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "custom-error-messages")]
+    fn custom_error() {
+        struct Invalid;
+
+        impl ser::Serialize for Invalid {
+            fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ser::Serializer,
+            {
+                Err(ser::Error::custom("invalid value"))
+            }
+        }
+
+        assert_eq!(
+            crate::to_string::<N, _>(&[Invalid]),
+            Err(crate::ser::Error::CustomWithMessage("invalid value".into()))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "custom-error-messages")]
+    fn truncate_error_message() {
+        use serde::ser::Error;
+        assert_eq!(
+            crate::ser::Error::custom("0123456789012345678901234567890123456789012345678901234567890123 <- after here the message should be truncated"),
+            crate::ser::Error::CustomWithMessage(
+                "0123456789012345678901234567890123456789012345678901234567890123".into()
+            )
+        );
+    }
 }
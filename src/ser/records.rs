@@ -0,0 +1,167 @@
+use serde::ser;
+
+use heapless::{ArrayLength, Vec};
+
+use crate::ser::{Result, Serializer};
+
+/// How records are delimited by a `RecordWriter`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordFormat {
+    /// Each record is followed by a newline (JSON Lines / NDJSON)
+    JsonLines,
+    /// Each record is preceded by a record separator (`0x1E`) and followed by a newline
+    /// (JSON text sequences, RFC 7464)
+    JsonTextSequence,
+}
+
+/// Appends JSON values to a buffer as newline-delimited records
+///
+/// A record that doesn't fit in the buffer is rolled back, so the buffer only ever holds complete
+/// records. Unlike with `to_vec`, control characters in strings are escaped, so a string holding a
+/// newline or a record separator can't split a record in two. Quotes and backslashes are written
+/// as they are, like with `to_vec`, since strings borrowed from a JSON document keep their escape
+/// sequences.
+///
+/// ```
+/// use heapless::consts::U16;
+/// use serde_json_core::ser::{RecordFormat, RecordWriter};
+///
+/// let mut writer = RecordWriter::<U16>::new(RecordFormat::JsonLines);
+/// writer.push(&[1, 2]).unwrap();
+/// writer.push(&true).unwrap();
+/// assert!(writer.push("doesn't fit").is_err());
+/// assert_eq!(writer.as_bytes(), b"[1,2]\ntrue\n");
+/// ```
+pub struct RecordWriter<B>
+where
+    B: ArrayLength<u8>,
+{
+    ser: Serializer<B>,
+    format: RecordFormat,
+}
+
+impl<B> RecordWriter<B>
+where
+    B: ArrayLength<u8>,
+{
+    /// Creates a new record writer
+    pub fn new(format: RecordFormat) -> Self {
+        let mut ser = Serializer::new();
+        ser.escape_control = true;
+        RecordWriter { ser, format }
+    }
+
+    /// Appends `value` as a new record
+    ///
+    /// If the record doesn't fit in the buffer, or fails to serialize, the buffer is left as it
+    /// was before the call.
+    pub fn push<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        let len = self.ser.buf.len();
+        let res = self.write_record(value);
+        if res.is_err() {
+            self.ser.truncate(len);
+        }
+        res
+    }
+
+    fn write_record<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        if self.format == RecordFormat::JsonTextSequence {
+            self.ser.buf.push(0x1e)?;
+        }
+        value.serialize(&mut self.ser)?;
+        self.ser.buf.push(b'\n')?;
+        Ok(())
+    }
+
+    /// Returns the records written so far
+    pub fn as_bytes(&self) -> &[u8] {
+        self.ser.as_bytes()
+    }
+
+    /// Discards all the records written so far
+    pub fn clear(&mut self) {
+        self.ser.truncate(0);
+    }
+
+    /// Consumes the writer and returns the buffer holding the records
+    pub fn into_inner(self) -> Vec<u8, B> {
+        self.ser.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_derive::Serialize;
+
+    use heapless::consts::{U32, U64};
+
+    use crate::ser::{Error, RecordFormat, RecordWriter};
+
+    #[derive(Serialize)]
+    struct Reading {
+        temp: i8,
+    }
+
+    #[test]
+    fn json_lines() {
+        let mut writer = RecordWriter::<U64>::new(RecordFormat::JsonLines);
+        writer.push(&Reading { temp: 20 }).unwrap();
+        writer.push(&Reading { temp: -3 }).unwrap();
+
+        assert_eq!(writer.as_bytes(), b"{\"temp\":20}\n{\"temp\":-3}\n");
+    }
+
+    #[test]
+    fn json_text_sequence() {
+        let mut writer = RecordWriter::<U64>::new(RecordFormat::JsonTextSequence);
+        writer.push(&Reading { temp: 20 }).unwrap();
+        writer.push("ok").unwrap();
+
+        assert_eq!(&writer.into_inner()[..], b"\x1e{\"temp\":20}\n\x1e\"ok\"\n");
+    }
+
+    #[test]
+    fn escaped_strings() {
+        let mut writer = RecordWriter::<U64>::new(RecordFormat::JsonTextSequence);
+        writer.push("two\nlines").unwrap();
+        writer.push(&["\x1e\t\u{1}é"]).unwrap();
+
+        assert_eq!(
+            writer.as_bytes(),
+            &b"\x1e\"two\\nlines\"\n\x1e[\"\\u001e\\t\\u0001\xc3\xa9\"]\n"[..]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let record = b"[\"a\\\"b\",\"c\\\\nd\"]\n";
+        let value: [&str; 2] = crate::from_slice(record).unwrap();
+
+        let mut writer = RecordWriter::<U32>::new(RecordFormat::JsonLines);
+        writer.push(&value).unwrap();
+        assert_eq!(writer.as_bytes(), &record[..]);
+    }
+
+    #[test]
+    fn rollback() {
+        let mut writer = RecordWriter::<U32>::new(RecordFormat::JsonLines);
+        writer.push(&Reading { temp: 20 }).unwrap();
+        writer.push(&Reading { temp: 21 }).unwrap();
+
+        // the value itself fits but its newline doesn't
+        assert_eq!(writer.push(&[1, 2, 3, 4]), Err(Error::BufferFull));
+        // unsupported values don't leave partial output behind either
+        assert_eq!(writer.push(&[()]), Err(Error::UnsupportedType));
+        assert_eq!(writer.as_bytes(), b"{\"temp\":20}\n{\"temp\":21}\n");
+
+        writer.clear();
+        writer.push(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(writer.as_bytes(), b"[1,2,3,4]\n");
+    }
+}
@@ -21,6 +21,8 @@ where
     }
 }
 
+// `T: ?Sized` is declared where serde's trait declares it
+#[allow(clippy::multiple_bound_locations)]
 impl<'a, B> ser::SerializeSeq for SerializeSeq<'a, B>
 where
     B: ArrayLength<u8>,
@@ -47,6 +49,8 @@ where
     }
 }
 
+// `T: ?Sized` is declared where serde's trait declares it
+#[allow(clippy::multiple_bound_locations)]
 impl<'a, B> ser::SerializeTuple for SerializeSeq<'a, B>
 where
     B: ArrayLength<u8>,
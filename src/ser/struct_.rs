@@ -21,6 +21,8 @@ where
     }
 }
 
+// `T: ?Sized` is declared where serde's trait declares it
+#[allow(clippy::multiple_bound_locations)]
 impl<'a, B> ser::SerializeStruct for SerializeStruct<'a, B>
 where
     B: ArrayLength<u8>,
@@ -0,0 +1,283 @@
+use serde::ser::{self, Serialize};
+
+use heapless::{consts::U32, ArrayLength, Vec};
+
+use crate::ser::{Error, Result, Serializer};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Container {
+    Array,
+    Object,
+}
+
+/// Writes a JSON document piece by piece, for messages whose shape is only known at run time
+///
+/// Commas are inserted automatically. Containers can be nested up to 32 levels deep; deeper
+/// nesting is reported as `Error::DepthLimitExceeded`. A call that fails, for example because the
+/// buffer is full, leaves the buffer and the writer as they were before the call.
+///
+/// Misuse, like ending more containers than were begun, writing a value into an object without a
+/// key or taking the buffer out while containers are still open, panics in debug builds.
+///
+/// ```
+/// use heapless::consts::U64;
+/// use serde_json_core::ser::JsonWriter;
+///
+/// let channels = [("temp", 21), ("humidity", 40)];
+///
+/// let mut writer = JsonWriter::<U64>::new();
+/// writer.begin_object().unwrap();
+/// for (name, value) in channels.iter() {
+///     writer.key(name).unwrap();
+///     writer.value(value).unwrap();
+/// }
+/// writer.key("tags").unwrap();
+/// writer.begin_array().unwrap();
+/// writer.value("indoor").unwrap();
+/// writer.end().unwrap();
+/// writer.end().unwrap();
+///
+/// assert_eq!(
+///     writer.as_bytes(),
+///     br#"{"temp":21,"humidity":40,"tags":["indoor"]}"#
+/// );
+/// ```
+pub struct JsonWriter<B>
+where
+    B: ArrayLength<u8>,
+{
+    ser: Serializer<B>,
+    stack: Vec<Container, U32>,
+    /// Nothing has been written into the innermost container, or at the top level, yet
+    first: bool,
+    /// An object key has been written and its value hasn't
+    after_key: bool,
+}
+
+impl<B> JsonWriter<B>
+where
+    B: ArrayLength<u8>,
+{
+    /// Creates a new writer
+    pub fn new() -> Self {
+        JsonWriter {
+            ser: Serializer::new(),
+            stack: Vec::new(),
+            first: true,
+            after_key: false,
+        }
+    }
+
+    /// Begins an object, as a value
+    pub fn begin_object(&mut self) -> Result<()> {
+        self.begin(Container::Object, b'{')
+    }
+
+    /// Begins an array, as a value
+    pub fn begin_array(&mut self) -> Result<()> {
+        self.begin(Container::Array, b'[')
+    }
+
+    /// Writes the key of the next member of the current object
+    pub fn key(&mut self, key: &str) -> Result<()> {
+        debug_assert!(
+            self.stack.last() == Some(&Container::Object) && !self.after_key,
+            "JsonWriter: key written outside of an object or after another key"
+        );
+
+        self.atomic(|w| {
+            if !w.first {
+                w.ser.buf.push(b',')?;
+            }
+            key.serialize(&mut w.ser)?;
+            w.ser.buf.push(b':')?;
+            w.first = false;
+            w.after_key = true;
+            Ok(())
+        })
+    }
+
+    /// Writes a value: a top-level value, an array element or the value of an object member
+    pub fn value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        self.atomic(|w| {
+            w.separator()?;
+            value.serialize(&mut w.ser)
+        })
+    }
+
+    /// Ends the innermost object or array
+    pub fn end(&mut self) -> Result<()> {
+        debug_assert!(
+            !self.stack.is_empty() && !self.after_key,
+            "JsonWriter: end without a matching begin, or after a key"
+        );
+
+        let close = match self.stack.last() {
+            Some(Container::Object) => b'}',
+            Some(Container::Array) => b']',
+            None => return Ok(()),
+        };
+        self.ser.buf.push(close)?;
+        self.stack.pop();
+        self.first = false;
+        Ok(())
+    }
+
+    /// Returns the number of objects and arrays that have been begun but not ended
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the JSON bytes written so far
+    pub fn as_bytes(&self) -> &[u8] {
+        self.ser.as_bytes()
+    }
+
+    /// Consumes the writer and returns the buffer holding the document
+    pub fn into_inner(self) -> Vec<u8, B> {
+        debug_assert!(
+            self.stack.is_empty(),
+            "JsonWriter: the document has unterminated objects or arrays"
+        );
+
+        self.ser.into_inner()
+    }
+
+    fn begin(&mut self, container: Container, open: u8) -> Result<()> {
+        self.atomic(|w| {
+            w.separator()?;
+            w.ser.buf.push(open)?;
+            w.stack
+                .push(container)
+                .map_err(|_| Error::DepthLimitExceeded)?;
+            w.first = true;
+            Ok(())
+        })
+    }
+
+    /// Writes the comma, if any, that goes before a value
+    fn separator(&mut self) -> Result<()> {
+        if self.after_key {
+            self.after_key = false;
+            return Ok(());
+        }
+
+        match self.stack.last() {
+            Some(Container::Array) => {
+                if !self.first {
+                    self.ser.buf.push(b',')?;
+                }
+            }
+            Some(Container::Object) => {
+                debug_assert!(false, "JsonWriter: object member written without a key")
+            }
+            None => debug_assert!(self.first, "JsonWriter: more than one top-level value"),
+        }
+        self.first = false;
+        Ok(())
+    }
+
+    /// Runs `f`, undoing everything it wrote if it fails
+    fn atomic<F>(&mut self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Self) -> Result<()>,
+    {
+        let (len, first, after_key) = (self.ser.buf.len(), self.first, self.after_key);
+        let res = f(self);
+        if res.is_err() {
+            self.ser.truncate(len);
+            self.first = first;
+            self.after_key = after_key;
+        }
+        res
+    }
+}
+
+impl<B> Default for JsonWriter<B>
+where
+    B: ArrayLength<u8>,
+{
+    fn default() -> Self {
+        JsonWriter::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use heapless::consts::{U16, U256, U64};
+
+    use crate::ser::{Error, JsonWriter};
+
+    #[test]
+    fn nested() {
+        let mut writer = JsonWriter::<U64>::new();
+        writer.begin_array().unwrap();
+        writer.begin_object().unwrap();
+        writer.end().unwrap();
+        writer.begin_object().unwrap();
+        writer.key("a").unwrap();
+        writer.begin_array().unwrap();
+        writer.end().unwrap();
+        writer.key("c").unwrap();
+        writer.value(&[1, 2]).unwrap();
+        writer.end().unwrap();
+        writer.value(&None::<u8>).unwrap();
+        assert_eq!(writer.depth(), 1);
+        writer.end().unwrap();
+
+        assert_eq!(&writer.into_inner()[..], br#"[{},{"a":[],"c":[1,2]},null]"#);
+    }
+
+    #[test]
+    fn top_level_value() {
+        let mut writer = JsonWriter::<U16>::new();
+        writer.value("hi").unwrap();
+        assert_eq!(writer.as_bytes(), b"\"hi\"");
+    }
+
+    #[test]
+    fn rollback() {
+        let mut writer = JsonWriter::<U16>::new();
+        writer.begin_array().unwrap();
+        writer.value(&1).unwrap();
+        assert_eq!(writer.value("does not fit"), Err(Error::BufferFull));
+        writer.value(&2).unwrap();
+        writer.end().unwrap();
+        assert_eq!(writer.as_bytes(), b"[1,2]");
+    }
+
+    #[test]
+    fn depth_limit() {
+        let mut writer = JsonWriter::<U256>::new();
+        for _ in 0..32 {
+            writer.begin_array().unwrap();
+        }
+        assert_eq!(writer.begin_array(), Err(Error::DepthLimitExceeded));
+        for _ in 0..32 {
+            writer.end().unwrap();
+        }
+        assert_eq!(writer.as_bytes().len(), 64);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "without a matching begin")]
+    fn unbalanced() {
+        let mut writer = JsonWriter::<U16>::new();
+        writer.begin_array().unwrap();
+        writer.end().unwrap();
+        let _ = writer.end();
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "without a key")]
+    fn value_without_key() {
+        let mut writer = JsonWriter::<U16>::new();
+        writer.begin_object().unwrap();
+        let _ = writer.value(&1);
+    }
+}
@@ -0,0 +1,520 @@
+//! A fixed-capacity, dynamically typed JSON value
+//!
+//! [`Value`] can hold any JSON document whose values fit in its capacity, without knowing its
+//! shape in advance. Strings are borrowed from the input. The values are stored as a flat list of
+//! nodes in a `heapless::Vec`; each scalar, array, object and object key takes up one node.
+//!
+//! [`Value`]: struct.Value.html
+//!
+//! ```
+//! use heapless::consts::{U16, U64};
+//! use serde_json_core::value::Value;
+//!
+//! let value: Value<'_, U16> =
+//!     serde_json_core::from_str(r#"{"ssid": "home", "channels": [1, 6, 11]}"#).unwrap();
+//!
+//! let root = value.root();
+//! assert_eq!(root.get("ssid").unwrap().as_str(), Some("home"));
+//! assert_eq!(root.get("channels").unwrap().get_index(2).unwrap().as_u64(), Some(11));
+//!
+//! let json = serde_json_core::to_string::<U64, _>(&value).unwrap();
+//! assert_eq!(json, r#"{"ssid":"home","channels":[1,6,11]}"#);
+//! ```
+
+use core::fmt;
+
+use heapless::{ArrayLength, Vec};
+use serde::de::{self, DeserializeSeed};
+use serde::ser::{self, SerializeMap, SerializeSeq};
+
+mod raw;
+
+pub use self::raw::RawValue;
+pub(crate) use self::raw::RAW_VALUE_TOKEN;
+
+/// What a `Value` that runs out of nodes reports as the unexpected value to `invalid_value`, so
+/// that this crate's deserializer can tell it apart and return `Error::TooManyNodes`
+pub(crate) const TOO_MANY_NODES: &str = "more nodes than fit";
+
+/// A JSON number
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Number {
+    /// A non-negative integer
+    PosInt(u64),
+    /// A negative integer
+    NegInt(i64),
+    /// A number with a fraction or an exponent, or an integer that doesn't fit in the others
+    Float(f64),
+}
+
+/// One node of a `Value`
+///
+/// This type is only public because it appears in the bounds of `Value`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Node<'a>(NodeKind<'a>);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum NodeKind<'a> {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(&'a str),
+    /// `len` elements follow; `next` is the index of the node after the last element
+    Array {
+        len: usize,
+        next: usize,
+    },
+    /// `len` key / value pairs follow; `next` is the index of the node after the last value
+    Object {
+        len: usize,
+        next: usize,
+    },
+}
+
+/// A JSON value of up to `N` nodes
+pub struct Value<'a, N>
+where
+    N: ArrayLength<Node<'a>>,
+{
+    nodes: Vec<Node<'a>, N>,
+}
+
+impl<'a, N> Value<'a, N>
+where
+    N: ArrayLength<Node<'a>>,
+{
+    /// Returns the top-level value
+    pub fn root(&self) -> ValueRef<'_, 'a> {
+        ValueRef {
+            nodes: &self.nodes,
+            index: 0,
+        }
+    }
+}
+
+impl<'a, N> fmt::Debug for Value<'a, N>
+where
+    N: ArrayLength<Node<'a>>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.nodes.fmt(f)
+    }
+}
+
+/// A reference to a value nested in a `Value`
+#[derive(Clone, Copy, Debug)]
+pub struct ValueRef<'v, 'a> {
+    nodes: &'v [Node<'a>],
+    index: usize,
+}
+
+impl<'v, 'a> ValueRef<'v, 'a> {
+    fn kind(&self) -> NodeKind<'a> {
+        self.nodes[self.index].0
+    }
+
+    fn at(&self, index: usize) -> ValueRef<'v, 'a> {
+        ValueRef {
+            nodes: self.nodes,
+            index,
+        }
+    }
+
+    /// Returns the index of the node that follows this value and all of its contents
+    fn next(&self) -> usize {
+        match self.kind() {
+            NodeKind::Array { next, .. } | NodeKind::Object { next, .. } => next,
+            _ => self.index + 1,
+        }
+    }
+
+    /// Returns `true` if this value is `null`
+    pub fn is_null(&self) -> bool {
+        self.kind() == NodeKind::Null
+    }
+
+    /// Returns the value of a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.kind() {
+            NodeKind::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a number
+    pub fn as_number(&self) -> Option<Number> {
+        match self.kind() {
+            NodeKind::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a non-negative integer
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.as_number()? {
+            Number::PosInt(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an integer that fits in an `i64`
+    #[allow(clippy::legacy_numeric_constants)]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.as_number()? {
+            Number::PosInt(n) if n <= i64::max_value() as u64 => Some(n as i64),
+            Number::NegInt(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a number as an `f64`
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.as_number()? {
+            Number::PosInt(n) => Some(n as f64),
+            Number::NegInt(n) => Some(n as f64),
+            Number::Float(n) => Some(n),
+        }
+    }
+
+    /// Returns the contents of a string, with escape sequences left as is
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self.kind() {
+            NodeKind::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of elements of an array or members of an object; `0` for other values
+    pub fn len(&self) -> usize {
+        match self.kind() {
+            NodeKind::Array { len, .. } | NodeKind::Object { len, .. } => len,
+            _ => 0,
+        }
+    }
+
+    /// Returns `true` if this value is not an array or object with at least one element / member
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Looks up the value of `key` in an object
+    pub fn get(&self, key: &str) -> Option<ValueRef<'v, 'a>> {
+        let len = match self.kind() {
+            NodeKind::Object { len, .. } => len,
+            _ => return None,
+        };
+
+        let mut index = self.index + 1;
+        for _ in 0..len {
+            let value = self.at(index + 1);
+            if self.at(index).as_str() == Some(key) {
+                return Some(value);
+            }
+            index = value.next();
+        }
+
+        None
+    }
+
+    /// Returns the `i`-th element of an array
+    pub fn get_index(&self, i: usize) -> Option<ValueRef<'v, 'a>> {
+        match self.kind() {
+            NodeKind::Array { len, .. } if i < len => {}
+            _ => return None,
+        }
+
+        let mut index = self.index + 1;
+        for _ in 0..i {
+            index = self.at(index).next();
+        }
+
+        Some(self.at(index))
+    }
+}
+
+impl<'v, 'a> ser::Serialize for ValueRef<'v, 'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self.kind() {
+            NodeKind::Null => serializer.serialize_none(),
+            NodeKind::Bool(b) => serializer.serialize_bool(b),
+            NodeKind::Number(Number::PosInt(n)) => serializer.serialize_u64(n),
+            NodeKind::Number(Number::NegInt(n)) => serializer.serialize_i64(n),
+            NodeKind::Number(Number::Float(n)) => serializer.serialize_f64(n),
+            NodeKind::String(s) => serializer.serialize_str(s),
+            NodeKind::Array { len, .. } => {
+                let mut seq = serializer.serialize_seq(Some(len))?;
+                let mut index = self.index + 1;
+                for _ in 0..len {
+                    let element = self.at(index);
+                    seq.serialize_element(&element)?;
+                    index = element.next();
+                }
+                seq.end()
+            }
+            NodeKind::Object { len, .. } => {
+                let mut map = serializer.serialize_map(Some(len))?;
+                let mut index = self.index + 1;
+                for _ in 0..len {
+                    let value = self.at(index + 1);
+                    map.serialize_entry(&self.at(index), &value)?;
+                    index = value.next();
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'a, N> ser::Serialize for Value<'a, N>
+where
+    N: ArrayLength<Node<'a>>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        self.root().serialize(serializer)
+    }
+}
+
+impl<'de, N> de::Deserialize<'de> for Value<'de, N>
+where
+    N: ArrayLength<Node<'de>>,
+{
+    /// Values that have more than `N` nodes are reported as an `invalid_value` error, which this
+    /// crate's deserializer turns into `de::Error::TooManyNodes`
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let mut nodes = Vec::new();
+        NodeSeed { nodes: &mut nodes }.deserialize(deserializer)?;
+        Ok(Value { nodes })
+    }
+}
+
+/// Deserializes a value into nodes appended to `nodes`
+struct NodeSeed<'v, 'a, N>
+where
+    N: ArrayLength<Node<'a>>,
+{
+    nodes: &'v mut Vec<Node<'a>, N>,
+}
+
+impl<'v, 'a, N> NodeSeed<'v, 'a, N>
+where
+    N: ArrayLength<Node<'a>>,
+{
+    fn push<E>(&mut self, node: NodeKind<'a>) -> Result<usize, E>
+    where
+        E: de::Error,
+    {
+        let index = self.nodes.len();
+        self.nodes
+            .push(Node(node))
+            .map_err(|_| E::invalid_value(de::Unexpected::Other(TOO_MANY_NODES), &*self))?;
+        Ok(index)
+    }
+
+    fn reborrow(&mut self) -> NodeSeed<'_, 'a, N> {
+        NodeSeed {
+            nodes: &mut *self.nodes,
+        }
+    }
+}
+
+impl<'v, 'de, N> DeserializeSeed<'de> for NodeSeed<'v, 'de, N>
+where
+    N: ArrayLength<Node<'de>>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'v, 'de, N> de::Visitor<'de> for NodeSeed<'v, 'de, N>
+where
+    N: ArrayLength<Node<'de>>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a JSON value of at most {} nodes", N::to_usize())
+    }
+
+    fn visit_bool<E>(mut self, v: bool) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.push(NodeKind::Bool(v)).map(drop)
+    }
+
+    fn visit_i64<E>(mut self, v: i64) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let n = if v < 0 {
+            Number::NegInt(v)
+        } else {
+            Number::PosInt(v as u64)
+        };
+        self.push(NodeKind::Number(n)).map(drop)
+    }
+
+    fn visit_u64<E>(mut self, v: u64) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.push(NodeKind::Number(Number::PosInt(v))).map(drop)
+    }
+
+    fn visit_f64<E>(mut self, v: f64) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.push(NodeKind::Number(Number::Float(v))).map(drop)
+    }
+
+    fn visit_borrowed_str<E>(mut self, v: &'de str) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.push(NodeKind::String(v)).map(drop)
+    }
+
+    fn visit_unit<E>(mut self) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.push(NodeKind::Null).map(drop)
+    }
+
+    fn visit_none<E>(self) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(mut self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let index = self.push(NodeKind::Array { len: 0, next: 0 })?;
+        let mut len = 0;
+        while seq.next_element_seed(self.reborrow())?.is_some() {
+            len += 1;
+        }
+
+        let next = self.nodes.len();
+        self.nodes[index] = Node(NodeKind::Array { len, next });
+        Ok(())
+    }
+
+    fn visit_map<A>(mut self, mut map: A) -> Result<(), A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let index = self.push(NodeKind::Object { len: 0, next: 0 })?;
+        let mut len = 0;
+        while let Some(key) = map.next_key::<&'de str>()? {
+            self.push(NodeKind::String(key))?;
+            map.next_value_seed(self.reborrow())?;
+            len += 1;
+        }
+
+        let next = self.nodes.len();
+        self.nodes[index] = Node(NodeKind::Object { len, next });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use heapless::consts::{U128, U4, U64};
+
+    use super::{Number, Value};
+    use crate::de::Error;
+
+    #[test]
+    fn scalars() {
+        let value: Value<'_, U4> = crate::from_str("null").unwrap();
+        assert!(value.root().is_null());
+
+        let value: Value<'_, U4> = crate::from_str(" true ").unwrap();
+        assert_eq!(value.root().as_bool(), Some(true));
+
+        let value: Value<'_, U4> = crate::from_str("-17").unwrap();
+        assert_eq!(value.root().as_number(), Some(Number::NegInt(-17)));
+        assert_eq!(value.root().as_i64(), Some(-17));
+        assert_eq!(value.root().as_u64(), None);
+
+        let value: Value<'_, U4> = crate::from_str("2.5e1").unwrap();
+        assert_eq!(value.root().as_f64(), Some(25.));
+
+        // integers that don't fit in an `i64` / `u64` are kept as floats
+        let value: Value<'_, U4> = crate::from_str("100000000000000000000").unwrap();
+        assert_eq!(value.root().as_number(), Some(Number::Float(1e20)));
+        let value: Value<'_, U4> = crate::from_str("-10000000000000000000").unwrap();
+        assert_eq!(value.root().as_f64(), Some(-1e19));
+
+        let value: Value<'_, U4> = crate::from_str(r#""hi""#).unwrap();
+        assert_eq!(value.root().as_str(), Some("hi"));
+    }
+
+    #[test]
+    fn nested() {
+        let input = r#"{"a": [1, {"b": null}, []], "c": {"d": "e"}, "f": false}"#;
+        let value: Value<'_, U64> = crate::from_str(input).unwrap();
+        let root = value.root();
+
+        assert_eq!(root.len(), 3);
+        let a = root.get("a").unwrap();
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.get_index(0).unwrap().as_u64(), Some(1));
+        assert!(a.get_index(1).unwrap().get("b").unwrap().is_null());
+        assert!(a.get_index(2).unwrap().is_empty());
+        assert!(a.get_index(3).is_none());
+        assert_eq!(root.get("c").unwrap().get("d").unwrap().as_str(), Some("e"));
+        assert_eq!(root.get("f").unwrap().as_bool(), Some(false));
+        assert!(root.get("g").is_none());
+    }
+
+    #[test]
+    fn round_trip() {
+        let input = r#"{"a":[1,-2,{"b":null}],"c":{},"d":"e","f":true}"#;
+        let value: Value<'_, U64> = crate::from_str(input).unwrap();
+        assert_eq!(crate::to_string::<U128, _>(&value).unwrap(), input);
+    }
+
+    #[test]
+    fn capacity() {
+        assert_eq!(
+            crate::from_str::<Value<'_, U4>>("[1, 2, 3]").map(drop),
+            Ok(())
+        );
+        assert_eq!(
+            crate::from_str::<Value<'_, U4>>("[1, 2, 3, 4]").map(drop),
+            Err(Error::TooManyNodes)
+        );
+        assert_eq!(
+            crate::from_str::<Value<'_, U4>>(r#"{"a": 1, "b": 2}"#).map(drop),
+            Err(Error::TooManyNodes)
+        );
+    }
+}
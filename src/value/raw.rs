@@ -0,0 +1,199 @@
+use core::fmt;
+
+use serde::{de, ser};
+
+use crate::de::{validate, Deserializer, Result};
+use crate::Config;
+
+/// The newtype struct name through which `RawValue` talks to this crate's (de)serializer
+pub(crate) const RAW_VALUE_TOKEN: &str = "$serde_json_core::private::RawValue";
+
+/// The unparsed JSON text of a single value, borrowed from the input
+///
+/// As a field of a struct, a `RawValue` captures the exact bytes of whatever value appears in its
+/// place, so that it can be deserialized later, once the rest of the struct says what type it has.
+/// It is serialized by writing those bytes verbatim. Only this crate's `Serializer` and
+/// `Deserializer` support `RawValue`.
+///
+/// The configuration of the deserializer a `RawValue` comes from is not kept: `parse` uses the
+/// default configuration, so values captured in lenient mode, or that should be checked against
+/// limits, have to be parsed with `parse_with_config`.
+///
+/// ```
+/// use serde_derive::Deserialize;
+/// use serde_json_core::value::RawValue;
+///
+/// #[derive(Deserialize)]
+/// struct Envelope<'a> {
+///     kind: &'a str,
+///     #[serde(borrow)]
+///     payload: RawValue<'a>,
+/// }
+///
+/// let envelope: Envelope<'_> =
+///     serde_json_core::from_str(r#"{"kind":"rgb","payload":[255, 128, 0]}"#).unwrap();
+/// assert_eq!(envelope.payload.get(), "[255, 128, 0]");
+///
+/// if envelope.kind == "rgb" {
+///     let rgb: [u8; 3] = envelope.payload.parse().unwrap();
+///     assert_eq!(rgb, [255, 128, 0]);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RawValue<'a> {
+    json: &'a str,
+}
+
+impl<'a> RawValue<'a> {
+    /// Wraps `json`, which is checked to be a single well-formed JSON value like `de::validate`
+    /// does
+    ///
+    /// Whitespace around the value is kept, and written out when the `RawValue` is serialized.
+    pub fn new(json: &'a str) -> Result<Self> {
+        validate(json.as_bytes())?;
+        Ok(RawValue { json })
+    }
+
+    /// `json` must hold exactly one JSON value, without surrounding whitespace
+    pub(crate) fn new_unchecked(json: &'a str) -> Self {
+        RawValue { json }
+    }
+
+    /// Returns the JSON text of this value
+    pub fn get(&self) -> &'a str {
+        self.json
+    }
+
+    /// Deserializes this value as a `T`, with the default configuration
+    pub fn parse<T>(&self) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        self.parse_with_config(Config::new())
+    }
+
+    /// Deserializes this value as a `T`, with the given configuration
+    pub fn parse_with_config<T>(&self, config: Config) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        let mut de = Deserializer::with_config(self.json.as_bytes(), config);
+        let value = de::Deserialize::deserialize(&mut de)?;
+        de.end()?;
+
+        Ok(value)
+    }
+}
+
+impl<'a> ser::Serialize for RawValue<'a> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(RAW_VALUE_TOKEN, self.json)
+    }
+}
+
+impl<'de: 'a, 'a> de::Deserialize<'de> for RawValue<'a> {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(RAW_VALUE_TOKEN, RawValueVisitor)
+    }
+}
+
+struct RawValueVisitor;
+
+impl<'de> de::Visitor<'de> for RawValueVisitor {
+    type Value = RawValue<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> core::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(RawValue::new_unchecked(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use heapless::consts::U64;
+    use serde_derive::{Deserialize, Serialize};
+
+    use serde::de::IgnoredAny;
+
+    use super::RawValue;
+    use crate::de::Error;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Envelope<'a> {
+        id: u32,
+        #[serde(borrow)]
+        payload: RawValue<'a>,
+    }
+
+    #[test]
+    fn capture() {
+        let envelope: Envelope<'_> =
+            crate::from_str(r#"{"id":1,"payload":{"a": [1, "}"]}}"#).unwrap();
+        assert_eq!(envelope.payload.get(), r#"{"a": [1, "}"]}"#);
+
+        let envelope: Envelope<'_> = crate::from_str(r#"{"payload": -1.5e3 , "id": 2}"#).unwrap();
+        assert_eq!(envelope.payload.get(), "-1.5e3");
+        assert_eq!(envelope.payload.parse(), Ok(-1500.0f32));
+
+        assert_eq!(
+            crate::from_str::<Envelope<'_>>(r#"{"id":1,"payload":}"#),
+            Err(Error::ExpectedSomeValue)
+        );
+    }
+
+    #[test]
+    fn new() {
+        assert_eq!(RawValue::new(" [1, 2] ").map(|v| v.get()), Ok(" [1, 2] "));
+        assert_eq!(RawValue::new("[1, 2"), Err(Error::EofWhileParsingList));
+        assert_eq!(RawValue::new("1 2"), Err(Error::TrailingCharacters));
+    }
+
+    #[test]
+    fn config() {
+        use serde::Deserialize;
+
+        use crate::de::Deserializer;
+        use crate::Config;
+
+        let config = Config::new().lenient(true);
+        let input = br#"{id: 1, payload: {'a': [1, 2,]}}"#;
+        let mut de = Deserializer::with_config(input, config);
+        let envelope = Envelope::deserialize(&mut de).unwrap();
+        assert_eq!(envelope.payload.get(), "{'a': [1, 2,]}");
+
+        // the deserializer's configuration is not kept
+        assert!(envelope.payload.parse::<IgnoredAny>().is_err());
+        assert!(envelope
+            .payload
+            .parse_with_config::<IgnoredAny>(config)
+            .is_ok());
+
+        // `RawValue` can still be deserialized through its trait
+        let mut de = Deserializer::new(b"[1]");
+        assert_eq!(RawValue::deserialize(&mut de).map(|v| v.get()), Ok("[1]"));
+    }
+
+    #[test]
+    fn verbatim() {
+        let envelope = Envelope {
+            id: 3,
+            payload: RawValue::new(r#"{"x": [1, 2]}"#).unwrap(),
+        };
+        assert_eq!(
+            &*crate::to_string::<U64, _>(&envelope).unwrap(),
+            r#"{"id":3,"payload":{"x": [1, 2]}}"#
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Runs the vendored subset of the JSONTestSuite cases against the deserializer in strict mode
+
+use std::fs;
+use std::path::Path;
+
+use heapless::consts::U64;
+use serde::de::{Deserialize, IgnoredAny};
+use serde_json_core::de::{Deserializer, Result};
+use serde_json_core::value::Value;
+use serde_json_core::Config;
+
+fn parse<'a, T>(input: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let mut de = Deserializer::with_config(input, Config::new().strict(true));
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+
+    Ok(value)
+}
+
+#[test]
+fn json_test_suite() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/JSONTestSuite/test_parsing");
+
+    let mut cases = 0;
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_str().unwrap().to_owned();
+        let input = fs::read(&path).unwrap();
+
+        let accept = if name.starts_with("y_") {
+            true
+        } else if name.starts_with("n_") {
+            false
+        } else {
+            continue;
+        };
+
+        let value = parse::<Value<'_, U64>>(&input);
+        assert_eq!(
+            value.is_ok(),
+            accept,
+            "{} as a Value: {:?}",
+            name,
+            value.err()
+        );
+
+        let ignored = parse::<IgnoredAny>(&input);
+        assert_eq!(
+            ignored.is_ok(),
+            accept,
+            "{} skipped: {:?}",
+            name,
+            ignored.err()
+        );
+
+        let mut de = Deserializer::with_config(&input, Config::new().strict(true));
+        let valid = de.validate();
+        assert_eq!(
+            valid.is_ok(),
+            accept,
+            "{} validated: {:?}",
+            name,
+            valid.err()
+        );
+
+        cases += 1;
+    }
+
+    assert!(cases > 0);
+}